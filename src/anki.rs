@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+use std::io::Write;
+
+use crate::error::Result;
+use crate::kana::lookup_entry;
+use crate::report::{rank_weakest, Report, ReportFilter};
+use crate::types::*;
+
+pub const DEFAULT_CARD_COUNT: usize = 20;
+const MISTAKES_PER_CARD: usize = 3;
+
+// Anki reads these header lines when importing a text file
+const DECK_HEADER: &str = "#separator:tab\n#html:false\n#columns:Kana\tRomaji\tCommon mistakes\tTags\n#tags column:4\n";
+
+// Tabs and line breaks would split the note
+fn tsv_field(value: &str) -> String {
+    value.replace(['\t', '\n', '\r'], " ")
+}
+
+/// The most frequent wrong answers, most frequent first, e.g. "ne (3), me (1)".
+fn common_mistakes(stats: &CharacterStats) -> String {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for mistake in &stats.mistakes {
+        *counts.entry(mistake.input.as_str()).or_default() += 1;
+    }
+    let mut counts: Vec<(&str, usize)> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    counts
+        .iter()
+        .take(MISTAKES_PER_CARD)
+        .map(|(input, count)| format!("{} ({})", input, count))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Writes the `count` weakest kana as tab-separated notes that Anki imports
+/// directly (File → Import). Returns the number of notes written.
+pub fn export_deck(
+    history: &UserHistory,
+    filter: ReportFilter,
+    romanization: Romanization,
+    count: usize,
+    mut out: impl Write,
+) -> Result<usize> {
+    let report = Report::build("", history, filter, romanization);
+    let stats = history.stats_for(filter.exercise);
+
+    out.write_all(DECK_HEADER.as_bytes())?;
+    let mut notes = 0;
+    for row in rank_weakest(&report.kana).into_iter().take(count) {
+        let romaji = lookup_entry(&row.kana)
+            .map(|entry| entry.readings(romanization).join(" / "))
+            .unwrap_or_else(|| row.romaji.clone());
+        let mistakes = stats.get(&row.kana).map(common_mistakes).unwrap_or_default();
+        writeln!(
+            out,
+            "{}\t{}\t{}\tkana_practice {} {}",
+            tsv_field(&row.kana),
+            tsv_field(&romaji),
+            tsv_field(&mistakes),
+            row.kana_type,
+            row.set,
+        )?;
+        notes += 1;
+    }
+
+    Ok(notes)
+}
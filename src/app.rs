@@ -1,8 +1,13 @@
 use std::collections::{BTreeSet, HashMap};
 
+use crate::clock::{Clock, SystemClock};
 use crate::error::{Result, KanaError};
+use crate::goals::{daily_totals, streak, Calendar, CALENDAR_HEIGHT, LEGEND};
 use crate::kana::*;
+use crate::romaji::{align, reverse_prompt, spells, to_kana, to_romaji, transliterate};
+use crate::scheduler::{make_scheduler, Scheduler};
 use crate::types::*;
+use crate::words::{words_for, Word};
 use chrono::{DateTime, Utc};
 use ratatui::layout::Alignment;
 use ratatui::widgets::Axis;
@@ -11,25 +16,66 @@ use ratatui::{
     style::{Color, Modifier, Style},
     symbols,
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph, Chart, Dataset, GraphType},
+    widgets::{Block, Borders, Paragraph, Chart, Dataset, GraphType, Wrap},
     Frame,
 };
 use rand::distributions::{Distribution, WeightedIndex};
-use tracing::{debug, info, warn};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use tracing::{info, warn};
+
+const REVERSE_CHOICES: usize = 6;
+const MIN_CHOICE_OPTIONS: usize = 4;
+const MAX_CHOICE_OPTIONS: usize = 6;
+// Rows the response chart needs to stay readable below the start screen calendar
+const MIN_CHART_HEIGHT: u16 = 8;
+// How many earlier sessions the summary averages over
+const SUMMARY_SESSIONS: usize = 10;
+pub const DEFAULT_REVEAL_AFTER: u32 = 2;
+/// Answers slower than this are taken as the learner having looked away.
+pub const DEFAULT_IDLE_AFTER: std::time::Duration = std::time::Duration::from_secs(30);
 
 pub struct App {
     pub state: AppState,
     pub should_quit: bool,
+    scheduler: Box<dyn Scheduler>,
+    rng: StdRng,
+    clock: Box<dyn Clock>,
 }
 
 impl App {
     pub fn new() -> Self {
+        let state = AppState::default();
         Self {
-            state: AppState::default(),
+            scheduler: make_scheduler(state.scheduler),
+            state,
             should_quit: false,
+            rng: StdRng::from_entropy(),
+            clock: Box::new(SystemClock),
         }
     }
 
+    /// Seeds every random choice in the session. Together with the same history
+    /// file, clock readings and inputs, this reproduces the exact kana sequence.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+        // Schedulers may keep state such as a shuffled deck
+        self.scheduler = make_scheduler(self.state.scheduler);
+    }
+
+    pub fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    pub fn set_idle_after(&mut self, idle_after: std::time::Duration) {
+        self.state.idle_after_ms = idle_after.as_millis() as f64;
+    }
+
+    pub fn set_reveal_after(&mut self, failures: u32) {
+        self.state.reveal_after = failures;
+    }
+
     pub fn set_practice_mode(&mut self, mode: PracticeMode) {
         self.state.practice_mode = mode;
     }
@@ -50,118 +96,648 @@ impl App {
                     self.check_answer()?;
                 }
             }
+            AppMode::Feedback => self.dismiss_feedback()?,
+            AppMode::Summary => self.should_quit = true,
         }
         Ok(())
     }
 
-    fn get_current_kana_set(&self) -> &'static [(&'static str, &'static str)] {
-        match (self.state.kana_type, self.state.practice_mode) {
-            (KanaType::Hiragana, PracticeMode::Main) => &MAIN_HIRAGANA,
-            (KanaType::Hiragana, PracticeMode::Dakuten) => &DAKUTEN_HIRAGANA,
-            (KanaType::Hiragana, PracticeMode::Combination) => &COMBINATION_HIRAGANA,
-            (KanaType::Hiragana, PracticeMode::All) => &ALL_HIRAGANA,
-            (KanaType::Katakana, PracticeMode::Main) => &MAIN_KATAKANA,
-            (KanaType::Katakana, PracticeMode::Dakuten) => &DAKUTEN_KATAKANA,
-            (KanaType::Katakana, PracticeMode::Combination) => &COMBINATION_KATAKANA,
-            (KanaType::Katakana, PracticeMode::All) => &ALL_KATAKANA,
+    /// Files the session in progress in the history. Returns it, or `None`
+    /// if nothing was answered since it would have started.
+    pub fn end_session(&mut self) -> Option<SessionRecord> {
+        let session = self.state.session.take()?;
+        info!(
+            start = %session.start,
+            end = %session.end,
+            active_seconds = session.active_seconds,
+            attempts = session.attempts,
+            successes = session.successes,
+            "Session ended"
+        );
+        self.state.history.add_session(session.clone());
+        Some(session)
+    }
+
+    /// Ends the session and shows its summary. Returns false when there was
+    /// no session, so the caller can quit straight away.
+    pub fn show_summary(&mut self) -> bool {
+        if self.end_session().is_none() {
+            return false;
+        }
+        self.state.mode = AppMode::Summary;
+        self.state.feedback = None;
+        self.state.current_kana = None;
+        self.state.start_time = None;
+        self.state.input_buffer.clear();
+        self.state.show_mnemonic = false;
+        true
+    }
+
+    // Counts an answer towards the session in progress, starting one if needed
+    fn record_session_answer(&mut self, start_time: DateTime<Utc>, now: DateTime<Utc>, response_time: f64, success: bool) {
+        let state = &mut self.state;
+        let session = state.session.get_or_insert(SessionRecord {
+            start: start_time,
+            end: now,
+            active_seconds: 0.0,
+            attempts: 0,
+            successes: 0,
+            kana_type: state.kana_type,
+            practice_mode: state.practice_mode,
+            exercise: state.exercise,
+        });
+        session.end = now;
+        session.active_seconds += response_time.min(state.idle_after_ms) / 1000.0;
+        session.attempts += 1;
+        session.successes += u32::from(success);
+    }
+
+    /// Leaves the feedback screen. A correct answer moves on to the next kana,
+    /// a wrong one retries the same kana. Either way the clock starts now.
+    pub fn dismiss_feedback(&mut self) -> Result<()> {
+        let Some(feedback) = self.state.feedback.take() else {
+            return Ok(());
+        };
+        self.state.mode = AppMode::Ready;
+        if feedback.success {
+            self.select_next_kana()
+        } else {
+            // Once the answer has been shown, the retry counts as fully hinted
+            if feedback.revealed {
+                let answer_len = self.state.expected_romaji.as_deref().map_or(0, |romaji| romaji.chars().count());
+                self.state.hints = self.state.hints.max(answer_len as u32);
+            }
+            // The mnemonic shown for the mistake would be a free hint on the retry
+            self.state.show_mnemonic = self.state.mnemonic_hint;
+            self.start_timing(self.clock.now());
+            Ok(())
         }
     }
 
+    /// The mnemonic for the kana on screen. Words have none, and in reverse
+    /// mode it would give the answer away until that is revealed.
+    pub fn current_mnemonic(&self) -> Option<&str> {
+        let revealed = self.state.feedback.as_ref().is_some_and(|feedback| feedback.revealed);
+        match self.state.exercise {
+            ExerciseMode::Words => return None,
+            ExerciseMode::Reverse if !revealed => return None,
+            _ => {}
+        }
+        self.state.current_kana
+            .as_deref()
+            .and_then(|kana| self.state.mnemonics.get(kana))
+    }
+
+    /// Shows or hides the mnemonic panel while answering. Opening it counts as a hint.
+    pub fn toggle_mnemonic(&mut self) {
+        if self.state.mode != AppMode::Ready || self.current_mnemonic().is_none() {
+            return;
+        }
+        self.state.show_mnemonic = !self.state.show_mnemonic;
+        if self.state.show_mnemonic && !self.state.mnemonic_hint {
+            self.state.mnemonic_hint = true;
+            info!(kana = self.state.current_kana.as_deref(), "Mnemonic shown as a hint");
+        }
+    }
+
+    /// Starts the response time of the prompt on screen. Prompts picked while
+    /// the start or pause screen is up aren't visible yet, so they aren't timed.
+    fn start_timing(&mut self, now: DateTime<Utc>) {
+        self.state.start_time = (self.state.mode == AppMode::Ready).then_some(now);
+        self.state.keystrokes.clear();
+    }
+
+    fn record_keystroke(&mut self, backspace: bool) {
+        if let Some(start_time) = self.state.start_time {
+            let at_ms = (self.clock.now() - start_time).num_milliseconds() as f64;
+            self.state.keystrokes.push(Keystroke { at_ms, backspace });
+        }
+    }
+
+    pub fn handle_backspace(&mut self) {
+        if self.state.input_buffer.pop().is_some() {
+            self.record_keystroke(true);
+        }
+    }
+
+    /// Shows the correct answer for the wrong answer on screen.
+    pub fn reveal_answer(&mut self) {
+        if let Some(feedback) = self.state.feedback.as_mut().filter(|feedback| !feedback.success) {
+            feedback.revealed = true;
+            self.state.show_mnemonic = self.current_mnemonic().is_some();
+        }
+    }
+
+    /// Reveals one more letter of the expected romaji. Reverse mode has no
+    /// hints, since the romaji is already the prompt.
+    pub fn take_hint(&mut self) {
+        if self.state.mode != AppMode::Ready || self.state.exercise == ExerciseMode::Reverse {
+            return;
+        }
+        let Some(expected) = self.state.expected_romaji.as_deref() else {
+            return;
+        };
+        if (self.state.hints as usize) < expected.chars().count() {
+            self.state.hints += 1;
+            info!(
+                kana = self.state.current_kana.as_deref(),
+                hints = self.state.hints,
+                "Hint taken"
+            );
+        }
+    }
+
+    /// The answer as shown on the feedback screen: every accepted reading in the
+    /// romaji exercises, the kana itself in reverse mode.
+    fn correct_answer(&self) -> String {
+        match self.state.exercise {
+            ExerciseMode::Reverse => self.state.current_kana.clone().unwrap_or_default(),
+            ExerciseMode::Romaji | ExerciseMode::Choice => self.state.current_kana
+                .as_deref()
+                .and_then(|kana| self.find_kana_entry(kana))
+                .map(|entry| entry.readings(self.state.romanization).join(" / "))
+                .or_else(|| self.state.expected_romaji.clone())
+                .unwrap_or_default(),
+            ExerciseMode::Words => self.state.expected_romaji.clone().unwrap_or_default(),
+        }
+    }
+
+    /// Settings hotkeys on the start and pause screens: `k` kana type, `s` practice
+    /// set, `o` scheduler (order), `r` romanization, `i` instant submission.
+    /// Returns whether `c` was one of them.
+    pub fn handle_settings_key(&mut self, c: char) -> bool {
+        if !matches!(self.state.mode, AppMode::Initial | AppMode::Paused) {
+            return false;
+        }
+        // A session covers one kana type and set, so changing them starts a new one
+        if matches!(c, 'k' | 's') {
+            self.end_session();
+        }
+        match c {
+            'k' => self.set_kana_type(self.state.kana_type.next()),
+            's' => self.set_practice_mode(self.state.practice_mode.next()),
+            'o' => self.set_scheduler(self.state.scheduler.next(), self.state.due_only),
+            'r' => self.set_romanization(self.state.romanization.next()),
+            'i' => self.set_instant(!self.state.instant),
+            _ => return false,
+        }
+        // A "nothing due" notice may no longer hold for the new settings
+        self.state.notice = None;
+        info!(
+            kana_type = %self.state.kana_type,
+            practice_mode = %self.state.practice_mode,
+            scheduler = %self.state.scheduler,
+            romanization = %self.state.romanization,
+            instant = self.state.instant,
+            "Changed practice settings"
+        );
+        true
+    }
+
+    fn get_current_kana_set(&self) -> &'static [KanaEntry] {
+        kana_set(self.state.kana_type, self.state.practice_mode)
+    }
+
     pub fn set_kana_type(&mut self, kana_type: KanaType) {
         self.state.kana_type = kana_type;
     }
 
+    pub fn set_romanization(&mut self, romanization: Romanization) {
+        self.state.romanization = romanization;
+    }
+
+    pub fn set_exercise(&mut self, exercise: ExerciseMode) {
+        self.state.exercise = exercise;
+    }
+
+    pub fn set_instant(&mut self, instant: bool) {
+        self.state.instant = instant;
+    }
+
+    pub fn set_goal(&mut self, goal: DailyGoal) {
+        self.state.goal = goal;
+    }
+
+    pub fn set_scheduler(&mut self, scheduler: SchedulerKind, due_only: bool) {
+        self.state.scheduler = scheduler;
+        self.state.due_only = due_only;
+        self.scheduler = make_scheduler(scheduler);
+    }
+
     fn is_kana_in_current_mode(&self, kana: &str) -> bool {
         self.get_current_kana_set()
             .iter()
-            .any(|(k, _)| *k == kana)
+            .any(|entry| entry.kana == kana)
+    }
+
+    fn find_kana_entry(&self, kana: &str) -> Option<&'static KanaEntry> {
+        self.get_current_kana_set()
+            .iter()
+            .find(|entry| entry.kana == kana)
     }
 
     pub fn select_next_kana(&mut self) -> Result<()> {
+        if self.state.exercise == ExerciseMode::Words {
+            return self.select_next_word();
+        }
+
         let kana_set = self.get_current_kana_set();
-        let now = Utc::now();
-        
-        let weights: Vec<(f64, &str)> = kana_set
+        let now = self.clock.now();
+
+        for entry in kana_set {
+            self.state.history.stats_for_mut(self.state.exercise)
+                .entry(entry.kana.to_string())
+                .or_insert_with(CharacterStats::new);
+        }
+        let stats = self.state.history.stats_for(self.state.exercise);
+
+        let candidates: Vec<&'static KanaEntry> = kana_set
             .iter()
-            .map(|&(kana, _)| {
-                let stats = self.state.history.character_stats
-                    .entry(kana.to_string())
-                    .or_insert_with(CharacterStats::new);
-                
-                let weight = stats.calculate_weight(now);
-                (weight, kana)
-            })
+            .filter(|entry| !self.state.due_only || stats[entry.kana].is_due(now))
             .collect();
-    
-        // Early return if no kana available (shouldn't happen with proper initialization)
-        if weights.is_empty() {
+
+        if candidates.is_empty() {
+            if self.state.due_only && !kana_set.is_empty() {
+                self.pause_until_due(kana_set, now);
+                return Ok(());
+            }
             return Err(KanaError::Terminal(
                 "No kana available for current practice mode".to_string()
             ));
         }
-    
-        let min_weight = weights.iter()
-            .map(|(w, _)| *w)
-            .fold(f64::INFINITY, f64::min);
-        let max_weight = weights.iter()
-            .map(|(w, _)| *w)
-            .fold(f64::NEG_INFINITY, f64::max);
-        let range = max_weight - min_weight;
-        
-        // Normalize weights to range [1, 10] to ensure positive weights
-        let normalized_weights: Vec<f64> = if range > 0.0 {
-            weights.iter().map(|(w, _)| {
-                1.0 + 9.0 * (w - min_weight) / range
-            }).collect()
-        } else {
-            vec![1.0; weights.len()]
+
+        let selected = self.scheduler
+            .next(&candidates, stats, now, &mut self.rng)
+            .and_then(|idx| candidates.get(idx).copied())
+            .ok_or_else(|| KanaError::Terminal(format!(
+                "Scheduler '{}' returned no kana. Candidates: {}",
+                self.state.scheduler, candidates.len()
+            )))?;
+        let selected_idx = kana_set
+            .iter()
+            .position(|entry| entry.kana == selected.kana)
+            .unwrap_or_default();
+
+        let expected = match self.state.exercise {
+            ExerciseMode::Reverse => reverse_prompt(selected, self.state.kana_type, self.state.romanization),
+            _ => selected.romaji(self.state.romanization),
+        };
+        let choices = match self.state.exercise {
+            ExerciseMode::Romaji | ExerciseMode::Words => Vec::new(),
+            ExerciseMode::Reverse => Self::build_kana_choices(kana_set, selected_idx, &mut self.rng),
+            ExerciseMode::Choice => Self::build_romaji_choices(&self.state, kana_set, selected_idx, &mut self.rng),
         };
     
-        debug!(
+        info!(
             kana_type = ?self.state.kana_type,
             practice_mode = ?self.state.practice_mode,
+            selected_kana = selected.kana,
+            expected_romaji = expected,
+            romanization = %self.state.romanization,
+            exercise = %self.state.exercise,
             kana_set_size = kana_set.len(),
-            min_weight = min_weight,
-            max_weight = max_weight,
-            weights_range = range,
-            normalized_weights = ?normalized_weights,
-            "Weights normalization for kana selection"
+            scheduler = %self.state.scheduler,
+            candidates = candidates.len(),
+            "Selected kana details"
         );
     
-        let dist = WeightedIndex::new(&normalized_weights)
+        self.state.current_kana = Some(selected.kana.to_string());
+        self.state.expected_romaji = Some(expected.to_string());
+        self.state.current_word = None;
+        self.state.choices = choices;
+        self.start_timing(now);
+        self.state.notice = None;
+        self.state.failed_attempts = 0;
+        self.state.hints = 0;
+        self.state.show_mnemonic = false;
+        self.state.mnemonic_hint = false;
+    
+        Ok(())
+    }
+
+    fn pause_until_due(&mut self, kana_set: &[KanaEntry], now: DateTime<Utc>) {
+        let stats = self.state.history.stats_for(self.state.exercise);
+        let next_due = kana_set
+            .iter()
+            .filter_map(|entry| stats.get(entry.kana).and_then(|stats| stats.due))
+            .filter(|due| *due > now)
+            .min();
+
+        info!(next_due = ?next_due, "No kana due for review");
+
+        self.state.notice = Some(match next_due {
+            Some(due) => format!(
+                "Nothing due. Next review at {}",
+                due.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+            ),
+            None => "Nothing due for review".to_string(),
+        });
+        self.state.mode = AppMode::Paused;
+        self.state.current_kana = None;
+        self.state.expected_romaji = None;
+        self.state.start_time = None;
+        self.state.choices.clear();
+    }
+
+    /// Words whose practised kana all belong to the current set, or failing that,
+    /// words that contain at least one of them.
+    fn get_current_word_set(&self) -> Vec<&'static Word> {
+        let words = words_for(self.state.kana_type);
+        let practised = |word: &Word| {
+            transliterate(word.kana, self.state.romanization)
+                .into_iter()
+                .filter_map(|mora| mora.entry)
+                .collect::<Vec<_>>()
+        };
+
+        let in_set: Vec<&'static Word> = words
+            .iter()
+            .filter(|word| practised(word).iter().all(|entry| self.is_kana_in_current_mode(entry.kana)))
+            .collect();
+        if !in_set.is_empty() {
+            return in_set;
+        }
+
+        words
+            .iter()
+            .filter(|word| practised(word).iter().any(|entry| self.is_kana_in_current_mode(entry.kana)))
+            .collect()
+    }
+
+    fn select_next_word(&mut self) -> Result<()> {
+        let mut words = self.get_current_word_set();
+        let policy = self.state.romanization;
+        let now = self.clock.now();
+
+        // Like single kana, a due-only session keeps to words with a kana due for review
+        if self.state.due_only {
+            let stats = &self.state.history.character_stats;
+            words.retain(|word| {
+                transliterate(word.kana, policy)
+                    .iter()
+                    .filter_map(|mora| mora.entry)
+                    .filter(|entry| self.is_kana_in_current_mode(entry.kana))
+                    .any(|entry| stats.get(entry.kana).is_none_or(|stats| stats.is_due(now)))
+            });
+            if words.is_empty() {
+                self.pause_until_due(self.get_current_kana_set(), now);
+                return Ok(());
+            }
+        }
+
+        // A word is as urgent as the average of the kana it contains
+        let weights: Vec<f64> = words
+            .iter()
+            .map(|word| {
+                let mora_weights: Vec<f64> = transliterate(word.kana, policy)
+                    .iter()
+                    .filter_map(|mora| mora.entry)
+                    .map(|entry| {
+                        self.state.history.character_stats
+                            .get(entry.kana)
+                            .map_or(3.0, |stats| stats.calculate_weight(now))
+                    })
+                    .collect();
+                if mora_weights.is_empty() {
+                    1.0
+                } else {
+                    mora_weights.iter().sum::<f64>() / mora_weights.len() as f64
+                }
+            })
+            .collect();
+
+        let dist = WeightedIndex::new(&weights)
             .map_err(|e| KanaError::Terminal(format!(
-                "Failed to create weighted distribution: {}. Kana set size: {}", 
-                e, kana_set.len()
+                "Failed to create weighted distribution: {}. Word set size: {}",
+                e, words.len()
             )))?;
-        let mut rng = rand::thread_rng();
-        
-        let selected_idx = dist.sample(&mut rng);
-        let (selected_kana, selected_romaji) = kana_set.get(selected_idx)
-            .ok_or_else(|| KanaError::Terminal(format!(
-                "Invalid kana index: {}. Kana set size: {}", 
-                selected_idx, kana_set.len()
-            )))?;
-    
+        let word = words[dist.sample(&mut self.rng)];
+        let expected = to_romaji(word.kana, policy);
+
         info!(
             kana_type = ?self.state.kana_type,
             practice_mode = ?self.state.practice_mode,
-            selected_kana = selected_kana,
-            expected_romaji = selected_romaji,
-            kana_set_size = kana_set.len(),
-            selected_weight = weights[selected_idx].0,
-            "Selected kana details"
+            word = word.kana,
+            expected_romaji = expected,
+            word_set_size = words.len(),
+            "Selected word details"
         );
-    
-        self.state.current_kana = Some(selected_kana.to_string());
-        self.state.expected_romaji = Some(selected_romaji.to_string());
-        self.state.start_time = Some(now);
-    
+
+        self.state.current_kana = Some(word.kana.to_string());
+        self.state.current_word = Some(word);
+        self.state.expected_romaji = Some(expected);
+        self.state.choices = Vec::new();
+        self.start_timing(now);
+        self.state.failed_attempts = 0;
+        self.state.hints = 0;
+        self.state.show_mnemonic = false;
+        self.state.mnemonic_hint = false;
+
         Ok(())
     }
-    
 
-    pub fn handle_input(&mut self, c: char) {
+    /// Scores a word answer and records an attempt for every practised mora in it.
+    fn record_word_attempt(&mut self, input: &str, response_time: f64, idle: bool, now: DateTime<Utc>) -> bool {
+        let Some(word) = self.state.current_word else {
+            return false;
+        };
+
+        let morae = transliterate(word.kana, self.state.romanization);
+        let success = spells(&morae, input);
+        let results: Vec<(bool, String)> = if success {
+            morae.iter().map(|mora| (true, mora.readings[0].clone())).collect()
+        } else {
+            align(&morae, input)
+                .into_iter()
+                .map(|(matched, typed)| (matched, typed.to_string()))
+                .collect()
+        };
+
+        let practised = morae.iter().filter(|mora| mora.entry.is_some()).count().max(1);
+        let mora_response_time = response_time / practised as f64;
+        // Each mora gets its own slice of the answer time, so a kana that
+        // appears twice in a word still has distinct attempts
+        let mut mora_start = now - chrono::Duration::microseconds((response_time * 1000.0) as i64);
+        let mora_duration = chrono::Duration::microseconds((mora_response_time * 1000.0) as i64)
+            .max(chrono::Duration::microseconds(1));
+
+        for (mora, (matched, typed)) in morae.iter().zip(results) {
+            if let Some(entry) = mora.entry {
+                let start_time = mora_start;
+                mora_start += mora_duration;
+                self.state.history.character_stats
+                    .entry(entry.kana.to_string())
+                    .or_insert_with(CharacterStats::new)
+                    .record_attempt(TestEntry {
+                        input: typed,
+                        start_time,
+                        duration_ms: mora_response_time,
+                        success: matched,
+                        hints: self.state.hints,
+                        keystrokes: Vec::new(),
+                        idle,
+                    });
+            }
+        }
+
+        success
+    }
+
+    /// Picks the candidate grid for reverse mode: the answer plus random
+    /// distractors, leaving out homophones so every candidate reads differently.
+    fn build_kana_choices(
+        kana_set: &[KanaEntry],
+        answer_idx: usize,
+        rng: &mut impl Rng,
+    ) -> Vec<String> {
+        let mut others: Vec<&KanaEntry> = kana_set
+            .iter()
+            .enumerate()
+            .filter(|&(idx, _)| idx != answer_idx)
+            .map(|(_, entry)| entry)
+            .collect();
+        others.shuffle(rng);
+
+        let mut picked = vec![&kana_set[answer_idx]];
+        for entry in others {
+            if picked.len() >= REVERSE_CHOICES {
+                break;
+            }
+            if !picked.iter().any(|other| other.is_homophone(entry)) {
+                picked.push(entry);
+            }
+        }
+
+        let mut choices: Vec<String> = picked.iter().map(|entry| entry.kana.to_string()).collect();
+        choices.shuffle(rng);
+        choices
+    }
+
+    /// Picks the romaji options for multiple-choice mode.
+    ///
+    /// Distractors come from the learner's own past mistakes for this kana first,
+    /// then from visually similar kana, and are topped up at random. Kana the
+    /// learner still gets wrong often are shown with fewer options.
+    fn build_romaji_choices(
+        state: &AppState,
+        kana_set: &[KanaEntry],
+        answer_idx: usize,
+        rng: &mut impl Rng,
+    ) -> Vec<String> {
+        let answer = &kana_set[answer_idx];
+        let policy = state.romanization;
+        let stats = state.history.character_stats.get(answer.kana);
+
+        let option_count = match stats {
+            Some(stats) if stats.appearances > 0 && stats.exp_avg_accuracy >= 0.8 => MAX_CHOICE_OPTIONS,
+            Some(stats) if stats.appearances > 0 && stats.exp_avg_accuracy >= 0.5 => MAX_CHOICE_OPTIONS - 1,
+            _ => MIN_CHOICE_OPTIONS,
+        }
+        .min(kana_set.len());
+
+        // Only offer mistakes that are real readings, so typos don't become options
+        let known_romaji: BTreeSet<&str> = kana_set.iter().map(|entry| entry.romaji(policy)).collect();
+
+        let mut mistake_counts: HashMap<&str, usize> = HashMap::new();
+        if let Some(stats) = stats {
+            for mistake in &stats.mistakes {
+                if let Some(&romaji) = known_romaji.get(mistake.input.as_str()) {
+                    *mistake_counts.entry(romaji).or_default() += 1;
+                }
+            }
+        }
+        let mut from_mistakes: Vec<(&str, usize)> = mistake_counts.into_iter().collect();
+        from_mistakes.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+        let mut from_similar: Vec<&str> = similar_kana(answer.kana)
+            .filter_map(|kana| kana_set.iter().find(|entry| entry.kana == kana))
+            .map(|entry| entry.romaji(policy))
+            .collect();
+        from_similar.shuffle(rng);
+
+        let mut from_random: Vec<&str> = kana_set.iter().map(|entry| entry.romaji(policy)).collect();
+        from_random.shuffle(rng);
+
+        let mut choices = vec![answer.romaji(policy).to_string()];
+        let candidates = from_mistakes
+            .into_iter()
+            .map(|(romaji, _)| romaji)
+            .chain(from_similar)
+            .chain(from_random);
+        for romaji in candidates {
+            if choices.len() >= option_count {
+                break;
+            }
+            if !answer.accepts(romaji, policy) && !choices.iter().any(|choice| choice == romaji) {
+                choices.push(romaji.to_string());
+            }
+        }
+
+        choices.shuffle(rng);
+        choices
+    }
+
+    pub fn handle_input(&mut self, c: char) -> Result<()> {
+        if matches!(self.state.exercise, ExerciseMode::Choice | ExerciseMode::Reverse) {
+            let choice = c
+                .to_digit(10)
+                .and_then(|n| (n as usize).checked_sub(1))
+                .and_then(|idx| self.state.choices.get(idx))
+                .cloned();
+            if let Some(choice) = choice {
+                self.record_keystroke(false);
+                self.state.input_buffer = choice;
+                if self.check_answer()? && self.state.instant {
+                    self.dismiss_feedback()?;
+                }
+                return Ok(());
+            }
+            // Only the numbers are answers here; reverse mode also takes typed romaji
+            if self.state.exercise == ExerciseMode::Choice || c.is_ascii_digit() {
+                return Ok(());
+            }
+        }
+        self.record_keystroke(false);
         self.state.input_buffer.push(c);
+        self.submit_if_decided()
+    }
+
+    /// In instant mode, checks the answer as soon as the input decides it: when
+    /// it is an accepted reading that no longer reading in the set starts with,
+    /// or when no accepted reading starts with it any more. Correct answers go
+    /// straight to the next kana. Words still need Enter.
+    fn submit_if_decided(&mut self) -> Result<()> {
+        if !self.state.instant || self.state.mode != AppMode::Ready {
+            return Ok(());
+        }
+        // Reverse mode converts any romanization to kana
+        let policy = match self.state.exercise {
+            ExerciseMode::Romaji => self.state.romanization,
+            ExerciseMode::Reverse => Romanization::Any,
+            ExerciseMode::Choice | ExerciseMode::Words => return Ok(()),
+        };
+        let Some(entry) = self.state.current_kana.as_deref().and_then(|kana| self.find_kana_entry(kana)) else {
+            return Ok(());
+        };
+
+        let input = self.state.input_buffer.trim().to_lowercase();
+        if input.is_empty() {
+            return Ok(());
+        }
+        let readings = entry.readings(policy);
+        let complete = readings.iter().any(|romaji| *romaji == input);
+        let could_continue = readings.iter().any(|romaji| romaji.starts_with(&input));
+        // With ん and な in the set, "n" could still become "na"
+        let ambiguous = self.get_current_kana_set()
+            .iter()
+            .flat_map(|other| other.readings(policy))
+            .any(|romaji| romaji.len() > input.len() && romaji.starts_with(&input));
+
+        let decided = if complete { !ambiguous } else { !could_continue };
+        if decided && self.check_answer()? {
+            self.dismiss_feedback()?;
+        }
+        Ok(())
     }
 
     pub fn check_answer(&mut self) -> Result<bool> {
@@ -169,36 +745,94 @@ impl App {
             return Ok(false);
         }
 
-        if let (Some(ref expected), Some(start_time)) = (
-            self.state.expected_romaji.as_ref(),
+        if let (Some(expected), Some(start_time)) = (
+            self.state.expected_romaji.clone(),
             self.state.start_time
         ) {
-            let response_time = (Utc::now() - start_time).num_milliseconds() as f64;
+            let now = self.clock.now();
+            let response_time = (now - start_time).num_milliseconds() as f64;
+            let idle = response_time > self.state.idle_after_ms;
+            if idle {
+                info!(response_time = response_time, threshold = self.state.idle_after_ms, "Idle attempt, time not counted");
+            }
             let input = self.state.input_buffer.trim().to_lowercase();
-            let success = input == expected.to_lowercase();
+            let (input, success) = match self.state.exercise {
+                ExerciseMode::Romaji | ExerciseMode::Choice => {
+                    let success = match self.state.current_kana.as_deref().and_then(|kana| self.find_kana_entry(kana)) {
+                        Some(entry) => entry.accepts(&input, self.state.romanization),
+                        None => input == expected.to_lowercase(),
+                    };
+                    (input, success)
+                }
+                ExerciseMode::Reverse => {
+                    let input = to_kana(&input, self.state.kana_type);
+                    let success = self.state.current_kana.as_deref() == Some(input.as_str());
+                    (input, success)
+                }
+                ExerciseMode::Words => {
+                    let success = self.record_word_attempt(&input, response_time, idle, now);
+                    info!(
+                        word = self.state.current_kana.as_deref(),
+                        input = input,
+                        expected = expected,
+                        success = success,
+                        response_time = response_time,
+                        "Word answer checked"
+                    );
+                    (input, success)
+                }
+            };
 
-            if let Some(kana) = self.state.current_kana.as_ref() {
-                let stats = self.state.history.character_stats
+            let hints = self.state.hints + u32::from(self.state.mnemonic_hint);
+            let recorded_per_kana = self.state.exercise != ExerciseMode::Words;
+            if let Some(kana) = self.state.current_kana.as_ref().filter(|_| recorded_per_kana) {
+                let stats = self.state.history.stats_for_mut(self.state.exercise)
                     .entry(kana.to_string())
                     .or_insert_with(CharacterStats::new);
                 
-                stats.record_attempt(&input, success, response_time);
+                let keystrokes = std::mem::take(&mut self.state.keystrokes);
+                let first_key_ms = keystrokes.first().map(|key| key.at_ms);
+                stats.record_attempt(TestEntry {
+                    input: input.clone(),
+                    start_time,
+                    duration_ms: response_time,
+                    success,
+                    hints,
+                    keystrokes,
+                    idle,
+                });
                 
                 info!(
                     kana = kana,
                     input = input,
                     expected = expected,
                     success = success,
+                    hints = hints,
+                    first_key_ms = ?first_key_ms,
                     response_time = response_time,
+                    idle = idle,
                     ema_accuracy = stats.exp_avg_accuracy,
                     ema_response = stats.exp_avg_response,
                     "Answer checked"
                 );
             }
 
+            self.record_session_answer(start_time, now, response_time, success);
+            self.state.unsaved_answers += 1;
             self.state.input_buffer.clear();
-            if success {
-                self.select_next_kana()?;
+            if !success {
+                self.state.failed_attempts += 1;
+            }
+            let reveal_after = self.state.reveal_after;
+            self.state.feedback = Some(Feedback {
+                success,
+                input,
+                answer: self.correct_answer(),
+                revealed: reveal_after > 0 && self.state.failed_attempts >= reveal_after,
+            });
+            self.state.mode = AppMode::Feedback;
+            if !success {
+                self.state.show_mnemonic = self.current_mnemonic().is_some();
             }
 
             Ok(success)
@@ -209,6 +843,16 @@ impl App {
     }
 
     pub fn render(&self, f: &mut Frame) {
+        if self.state.mode == AppMode::Summary {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(3)])
+                .split(f.area());
+            self.render_summary(f, chunks[0]);
+            self.render_help(f, chunks[1]);
+            return;
+        }
+
         // Use percentage-based constraints for responsive layout
         let main_chunks = Layout::default()
             .direction(Direction::Vertical)
@@ -223,28 +867,228 @@ impl App {
     
         self.render_kana(f, main_chunks[0]);
         self.render_input(f, main_chunks[1]);
-        self.render_learning_progress(f, main_chunks[2]);
+        if self.state.mode == AppMode::Initial {
+            // Short screens get a one-line goal summary instead of the calendar,
+            // so the chart below keeps its room
+            let goal_height = if main_chunks[2].height >= CALENDAR_HEIGHT + 2 + MIN_CHART_HEIGHT {
+                CALENDAR_HEIGHT + 2
+            } else {
+                1
+            };
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(goal_height), Constraint::Min(0)])
+                .split(main_chunks[2]);
+            self.render_goal(f, chunks[0]);
+            self.render_learning_progress(f, chunks[1]);
+        } else {
+            self.render_learning_progress(f, main_chunks[2]);
+        }
         self.render_character_stats_split(f, main_chunks[3]);
         self.render_help(f, main_chunks[4]);
     }
     
+    /// Today's progress towards the daily goal and the streak, e.g.
+    /// "Today: 12/20 correct answers | Streak: 4 days (best 9)".
+    fn goal_status(&self) -> String {
+        let today = self.clock.now().with_timezone(&chrono::Local).date_naive();
+        let days = daily_totals(&self.state.history.sessions);
+        let streak = streak(&days, self.state.goal, today);
+        format!(
+            "Today: {}{} | Streak: {}",
+            self.state.goal.status(&days.get(&today).copied().unwrap_or_default()),
+            if streak.met_today { " ✓" } else { "" },
+            streak,
+        )
+    }
+
+    fn render_goal(&self, f: &mut Frame, area: Rect) {
+        if area.height < CALENDAR_HEIGHT + 2 {
+            f.render_widget(Paragraph::new(self.goal_status()), area);
+            return;
+        }
+        let today = self.clock.now().with_timezone(&chrono::Local).date_naive();
+        let days = daily_totals(&self.state.history.sessions);
+        let block = Block::default()
+            .title(self.goal_status())
+            .title_bottom(Line::from(LEGEND).right_aligned())
+            .borders(Borders::ALL);
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+        f.render_widget(Calendar::new(&days, self.state.goal, today), inner);
+    }
+
+    /// The session that just ended, next to the last session and the average
+    /// of recent ones that practised the same kana type, set and exercise.
+    fn render_summary(&self, f: &mut Frame, area: Rect) {
+        let Some((session, earlier)) = self.state.history.sessions.split_last() else {
+            return;
+        };
+        let previous: Vec<&SessionRecord> = earlier
+            .iter()
+            .rev()
+            .filter(|other| other.same_practice(session))
+            .take(SUMMARY_SESSIONS)
+            .collect();
+
+        let per_answer_ms = |session: &SessionRecord| session.active_seconds * 1000.0 / session.attempts.max(1) as f64;
+        let average = |value: &dyn Fn(&SessionRecord) -> f64| {
+            previous.iter().map(|session| value(session)).sum::<f64>() / previous.len().max(1) as f64
+        };
+
+        let mut lines = vec![
+            Line::from(format!(
+                "{} {} ({})  {} – {}",
+                session.kana_type,
+                session.practice_mode,
+                session.exercise,
+                session.start.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"),
+                session.end.with_timezone(&chrono::Local).format("%H:%M"),
+            )),
+            Line::from(""),
+        ];
+
+        // (label, this session, last time, average, whether higher is better, formatter)
+        type Row<'a> = (&'a str, f64, Option<f64>, f64, bool, fn(f64) -> String);
+        let last = previous.first().copied();
+        let rows: [Row; 4] = [
+            (
+                "Attempts",
+                session.attempts as f64,
+                last.map(|last| last.attempts as f64),
+                average(&|session| session.attempts as f64),
+                true,
+                |value| format!("{:.0}", value),
+            ),
+            (
+                "Accuracy",
+                session.accuracy(),
+                last.map(|last| last.accuracy()),
+                average(&|session| session.accuracy()),
+                true,
+                |value| format!("{:.1}%", value * 100.0),
+            ),
+            (
+                "Active time",
+                session.active_seconds,
+                last.map(|last| last.active_seconds),
+                average(&|session| session.active_seconds),
+                true,
+                format_seconds,
+            ),
+            (
+                "Per answer",
+                per_answer_ms(session),
+                last.map(per_answer_ms),
+                average(&per_answer_ms),
+                false,
+                |value| format!("{:.0}ms", value),
+            ),
+        ];
+
+        if previous.is_empty() {
+            lines.push(Line::from(format!("{:<12} {:>12}", "", "This session")));
+            for (label, value, _, _, _, format) in rows {
+                lines.push(Line::from(format!("{:<12} {:>12}", label, format(value))));
+            }
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                "First session with this practice set, nothing to compare yet",
+                Style::default().fg(Color::Gray),
+            )));
+        } else {
+            lines.push(Line::from(format!(
+                "{:<12} {:>12} {:>12} {:>12}",
+                "",
+                "This session",
+                "Last time",
+                format!("Avg of {}", previous.len()),
+            )));
+            for (label, value, last, average, higher_is_better, format) in rows {
+                let better = (value > average) == higher_is_better;
+                let color = if value == average {
+                    Color::White
+                } else if better {
+                    Color::Green
+                } else {
+                    Color::Red
+                };
+                lines.push(Line::from(vec![
+                    Span::raw(format!("{:<12} ", label)),
+                    Span::styled(format!("{:>12}", format(value)), Style::default().fg(color)),
+                    Span::raw(format!(" {:>12} {:>12}", last.map(format).unwrap_or_default(), format(average))),
+                ]));
+            }
+        }
+
+        lines.push(Line::from(""));
+        lines.push(Line::from(self.goal_status()));
+
+        let summary = Paragraph::new(lines)
+            .block(Block::default().title("Session Summary").borders(Borders::ALL))
+            .wrap(Wrap { trim: false });
+        f.render_widget(summary, area);
+    }
+
     fn render_kana(&self, f: &mut Frame, area: Rect) {
+        let (title, prompt) = match self.state.exercise {
+            ExerciseMode::Romaji | ExerciseMode::Choice => ("Current Kana", &self.state.current_kana),
+            ExerciseMode::Words => ("Current Word", &self.state.current_kana),
+            ExerciseMode::Reverse => ("Current Romaji", &self.state.expected_romaji),
+        };
         let kana_text = match self.state.mode {
-            AppMode::Initial => "Press Enter to start",
-            AppMode::Paused => "Press Enter to continue",
-            AppMode::Ready => self.state.current_kana
-                .as_ref()
-                .map(String::as_str)
-                .unwrap_or("Loading..."),
+            AppMode::Initial if self.state.profiles.len() > 1 => format!(
+                "Profile: {} (↑/↓ to switch) | Press Enter to start",
+                self.state.profile
+            ),
+            AppMode::Initial => format!("Profile: {} | Press Enter to start", self.state.profile),
+            AppMode::Paused => self.state.notice.as_deref().unwrap_or("Press Enter to continue").to_string(),
+            AppMode::Summary => "Session finished".to_string(),
+            AppMode::Ready | AppMode::Feedback => prompt
+                .as_deref()
+                .unwrap_or("Loading...")
+                .to_string(),
+        };
+        let showing_prompt = matches!(self.state.mode, AppMode::Ready | AppMode::Feedback);
+
+        let area = if !self.state.choices.is_empty() && showing_prompt {
+            let chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(area);
+            self.render_choices(f, chunks[1]);
+            chunks[0]
+        } else {
+            area
         };
         
         let block = Block::default()
-            .title("Current Kana")
+            .title(title)
             .borders(Borders::ALL);
             
-        let paragraph = Paragraph::new(Line::from(vec![
-            Span::styled(kana_text, Style::default().fg(Color::Cyan))
-        ]))
+        let mut spans = vec![Span::styled(kana_text, Style::default().fg(Color::Cyan))];
+        if let (true, Some(word)) = (showing_prompt, self.state.current_word) {
+            spans.push(Span::styled(format!("  ({})", word.meaning), Style::default().fg(Color::Gray)));
+        }
+        if let (Some(expected), true) = (&self.state.expected_romaji, showing_prompt && self.state.hints > 0 && self.state.exercise != ExerciseMode::Reverse) {
+            let shown: String = expected
+                .chars()
+                .enumerate()
+                .map(|(idx, c)| if idx < self.state.hints as usize { c } else { '_' })
+                .collect();
+            spans.push(Span::styled(format!("  hint: {}", shown), Style::default().fg(Color::Yellow)));
+        }
+        match &self.state.feedback {
+            Some(feedback) if feedback.success => {
+                spans.push(Span::styled(format!("  ✓ {}", feedback.answer), Style::default().fg(Color::Green)));
+            }
+            Some(feedback) if feedback.revealed => {
+                spans.push(Span::styled(format!("  = {}", feedback.answer), Style::default().fg(Color::Yellow)));
+            }
+            _ => {}
+        }
+            
+        let paragraph = Paragraph::new(Line::from(spans))
         .block(block)
         .alignment(Alignment::Center)  // Center horizontally
         .style(Style::default().add_modifier(Modifier::BOLD)); // Make text bold
@@ -252,40 +1096,120 @@ impl App {
         f.render_widget(paragraph, area);
     }
     
+    fn render_choices(&self, f: &mut Frame, area: Rect) {
+        let title = match self.state.exercise {
+            ExerciseMode::Choice => "Options",
+            _ => "Candidates",
+        };
+        let spans: Vec<Span> = self.state.choices
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, kana)| vec![
+                Span::styled(format!("{}:", idx + 1), Style::default().fg(Color::Gray)),
+                Span::styled(format!("{}  ", kana), Style::default().fg(Color::Cyan)),
+            ])
+            .collect();
+
+        let paragraph = Paragraph::new(Line::from(spans))
+            .block(Block::default().title(title).borders(Borders::ALL))
+            .alignment(Alignment::Center);
+
+        f.render_widget(paragraph, area);
+    }
+    
     fn render_input(&self, f: &mut Frame, area: Rect) {
+        let area = match self.current_mnemonic().filter(|_| self.state.show_mnemonic) {
+            Some(mnemonic) => {
+                let chunks = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .split(area);
+                self.render_mnemonic(f, chunks[1], mnemonic);
+                chunks[0]
+            }
+            None => area,
+        };
+
         let block = Block::default()
             .title("Input")
             .borders(Borders::ALL);
+
+        if let Some(feedback) = &self.state.feedback {
+            let (mark, color) = if feedback.success { ("✓", Color::Green) } else { ("✗", Color::Red) };
+            let mut spans = vec![Span::styled(
+                format!("{} {}", mark, feedback.input),
+                Style::default().fg(color).add_modifier(Modifier::BOLD),
+            )];
+            if !feedback.success && !feedback.revealed {
+                spans.push(Span::styled("  (? to show the answer)", Style::default().fg(Color::Gray)));
+            }
+            let input = Paragraph::new(Line::from(spans))
+                .block(block.border_style(Style::default().fg(color)))
+                .alignment(Alignment::Center);
+            f.render_widget(input, area);
+            return;
+        }
+
+        let mut spans = vec![Span::raw(self.state.input_buffer.as_str())];
+        if self.state.exercise == ExerciseMode::Reverse && !self.state.input_buffer.is_empty() {
+            spans.push(Span::raw(" → "));
+            spans.push(Span::styled(
+                to_kana(&self.state.input_buffer, self.state.kana_type),
+                Style::default().fg(Color::Cyan),
+            ));
+        }
             
-        let input = Paragraph::new(Line::from(vec![
-            Span::raw(&self.state.input_buffer)
-        ]))
+        let input = Paragraph::new(Line::from(spans))
         .block(block)
         .alignment(Alignment::Center);  // Center horizontally
             
         f.render_widget(input, area);
     }
     
+    fn render_mnemonic(&self, f: &mut Frame, area: Rect, mnemonic: &str) {
+        let text = Line::from(vec![
+            Span::styled(
+                format!("{}  ", self.state.current_kana.as_deref().unwrap_or_default()),
+                Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(mnemonic, Style::default().fg(Color::Yellow)),
+        ]);
+        let paragraph = Paragraph::new(text)
+            .block(Block::default().title("Mnemonic").borders(Borders::ALL))
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true });
+        f.render_widget(paragraph, area);
+    }
+
     fn render_learning_progress(&self, f: &mut Frame, area: Rect) {
-        let mut all_tests: Vec<(&DateTime<Utc>, f64)> = self.state.history.character_stats
+        let mut all_tests: Vec<&TestEntry> = self.state.history.stats_for(self.state.exercise)
             .iter()
             .filter(|(kana, _)| self.is_kana_in_current_mode(kana))
             .flat_map(|(_, stats)| &stats.test_history)
-            .map(|entry| (&entry.start_time, entry.duration_ms))
+            .filter(|entry| !entry.idle)
             .collect();
-        all_tests.sort_by_key(|(time, _)| *time);
+        all_tests.sort_by_key(|entry| entry.start_time);
 
         const ALPHA: f64 = 0.2;
         let mut ema_points: Vec<(f64, f64)> = Vec::new();
         let mut ema = 0.0;
+        // Recognition time, from the attempts that have keystrokes
+        let mut first_key_points: Vec<(f64, f64)> = Vec::new();
+        let mut first_key_ema = None;
 
-        for (idx, (_, duration)) in all_tests.iter().enumerate() {
+        for (idx, entry) in all_tests.iter().enumerate() {
             if idx == 0 {
-                ema = *duration;
+                ema = entry.duration_ms;
             } else {
-                ema = ALPHA * duration + (1.0 - ALPHA) * ema;
+                ema = ALPHA * entry.duration_ms + (1.0 - ALPHA) * ema;
             }
             ema_points.push((idx as f64, ema));
+
+            if let Some(first_key) = entry.first_key_ms() {
+                let next = first_key_ema.map_or(first_key, |avg| ALPHA * first_key + (1.0 - ALPHA) * avg);
+                first_key_ema = Some(next);
+                first_key_points.push((idx as f64, next));
+            }
         }
 
         if ema_points.is_empty() {
@@ -293,7 +1217,10 @@ impl App {
         }
 
         let mean = ema_points.iter().map(|(_, v)| *v).sum::<f64>() / ema_points.len() as f64;
-        let y_min = (mean * 0.5).max(0.0);
+        let y_min = first_key_points
+            .iter()
+            .map(|(_, v)| v * 0.9)
+            .fold((mean * 0.5).max(0.0), f64::min);
         let y_max = mean * 1.5;
         let y_step = (y_max - y_min) / 5.0;
 
@@ -315,13 +1242,22 @@ impl App {
             })
             .collect();
 
-        let dataset = Dataset::default()
+        let mut datasets = vec![Dataset::default()
+            .name("response")
             .marker(symbols::Marker::Dot)
             .graph_type(GraphType::Line)
             .style(Style::default().fg(Color::Cyan))
-            .data(&ema_points);
+            .data(&ema_points)];
+        if !first_key_points.is_empty() {
+            datasets.push(Dataset::default()
+                .name("first key")
+                .marker(symbols::Marker::Dot)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(Color::Magenta))
+                .data(&first_key_points));
+        }
 
-        let chart = Chart::new(vec![dataset])
+        let chart = Chart::new(datasets)
             .block(Block::default()
                 .title("Response Time Trend (EMA)")
                 .borders(Borders::ALL))
@@ -358,7 +1294,7 @@ impl App {
         let max_display_items = ((area.height as usize).saturating_sub(4)).min(100);
 
         // Get all stats sorted by EMA accuracy
-        let mut recent_stats: Vec<(&String, f64, f64, usize)> = self.state.history.character_stats
+        let mut recent_stats: Vec<(&String, f64, f64, usize)> = self.state.history.stats_for(self.state.exercise)
             .iter()
             .filter(|(kana, _)| self.is_kana_in_current_mode(kana))
             .map(|(kana, stats)| {
@@ -403,7 +1339,7 @@ impl App {
         );
     }
 
-    fn render_mistakes_column(&self, max_items: usize) -> Vec<Line> {
+    fn render_mistakes_column(&self, max_items: usize) -> Vec<Line<'_>> {
         let mut text = vec![
             Line::from(vec![
                 Span::styled("Recent Mistakes", Style::default().add_modifier(Modifier::BOLD))
@@ -414,13 +1350,17 @@ impl App {
         let current_kana_set = self.get_current_kana_set();
         let romaji_to_kana: HashMap<&str, &str> = current_kana_set
             .iter()
-            .map(|&(kana, romaji)| (romaji, kana))
+            .flat_map(|entry| {
+                entry.readings(self.state.romanization)
+                    .into_iter()
+                    .map(move |romaji| (romaji, entry.kana))
+            })
             .collect();
 
         let mut mistakes_map: HashMap<String, BTreeSet<String>> = HashMap::new();
         let mut latest_times: HashMap<String, DateTime<Utc>> = HashMap::new();
 
-        for (kana, stats) in &self.state.history.character_stats {
+        for (kana, stats) in self.state.history.stats_for(self.state.exercise) {
             if !self.is_kana_in_current_mode(kana) {
                 continue;
             }
@@ -434,7 +1374,7 @@ impl App {
 
                 mistakes_map
                     .entry(kana.clone())
-                    .or_insert_with(BTreeSet::new)
+                    .or_default()
                     .insert(wrong_input);
                 
                 latest_times
@@ -453,7 +1393,7 @@ impl App {
             ))
             .collect();
 
-        mistake_entries.sort_by(|a, b| b.2.cmp(&a.2));
+        mistake_entries.sort_by_key(|entry| std::cmp::Reverse(entry.2));
 
         for (kana, wrongs, _) in mistake_entries.into_iter().take(max_items) {
             let wrong_inputs = wrongs.into_iter().collect::<Vec<_>>().join(", ");
@@ -505,8 +1445,27 @@ impl App {
     }
 
     fn render_help(&self, f: &mut Frame, area: Rect) {
+        let settings_help = format!(
+            "ESC to quit | Enter to start | k: {} | s: {} | o: {} | r: {} | i: instant {}",
+            self.state.kana_type,
+            self.state.practice_mode,
+            self.state.scheduler,
+            self.state.romanization,
+            if self.state.instant { "on" } else { "off" },
+        );
         let help_text = Line::from(vec![
-            Span::raw("ESC to quit | Enter to submit | Type romaji for the shown kana")
+            Span::raw(match (self.state.mode, &self.state.feedback) {
+                (AppMode::Initial | AppMode::Paused, _) => settings_help.as_str(),
+                (AppMode::Summary, _) => "Enter or ESC to quit",
+                (AppMode::Feedback, Some(feedback)) if feedback.success => "ESC to quit | Enter for the next kana",
+                (AppMode::Feedback, _) => "ESC to quit | Enter to try again | ? to show the answer",
+                _ => match self.state.exercise {
+                    ExerciseMode::Romaji => "ESC to quit | Enter to submit | Tab for a hint | ? for a mnemonic | Type romaji for the shown kana",
+                    ExerciseMode::Reverse => "ESC to quit | Enter to submit | Type romaji to write the kana, or press 1-6 to pick it",
+                    ExerciseMode::Choice => "ESC to quit | Tab for a hint | ? for a mnemonic | Press the number of the matching romaji",
+                    ExerciseMode::Words => "ESC to quit | Enter to submit | Tab for a hint | Type the romaji for the whole word (n' before vowels)",
+                },
+            })
         ]);
         
         let help = Paragraph::new(help_text)
@@ -514,4 +1473,201 @@ impl App {
             
         f.render_widget(help, area);
     }
-}
\ No newline at end of file
+}
+
+fn format_seconds(seconds: f64) -> String {
+    let seconds = seconds.round() as u64;
+    if seconds >= 60 {
+        format!("{}m {:02}s", seconds / 60, seconds % 60)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn instant_mode_moves_on_after_a_correct_numbered_pick() {
+        for exercise in [ExerciseMode::Choice, ExerciseMode::Reverse] {
+            let mut app = App::new();
+            app.set_seed(1);
+            app.set_exercise(exercise);
+            app.set_instant(true);
+            app.handle_enter().unwrap();
+
+            let kana = app.state.current_kana.clone().unwrap();
+            let entry = lookup_entry(&kana).unwrap();
+            let idx = app.state.choices
+                .iter()
+                .position(|choice| *choice == kana || entry.accepts(choice, app.state.romanization))
+                .unwrap();
+            let digit = char::from_digit(idx as u32 + 1, 10).unwrap();
+            app.handle_input(digit).unwrap();
+
+            assert_eq!(app.state.mode, AppMode::Ready, "{}", exercise);
+            assert!(app.state.feedback.is_none());
+            assert_eq!(app.state.session.as_ref().map(|session| session.successes), Some(1));
+        }
+    }
+
+    #[test]
+    fn numbered_modes_ignore_keys_that_pick_no_choice() {
+        for exercise in [ExerciseMode::Choice, ExerciseMode::Reverse] {
+            let mut app = App::new();
+            app.set_seed(11);
+            app.set_exercise(exercise);
+            app.handle_enter().unwrap();
+
+            let past_last = char::from_digit(app.state.choices.len() as u32 + 1, 10).unwrap();
+            for c in ['0', past_last] {
+                app.handle_input(c).unwrap();
+            }
+            assert_eq!(app.state.input_buffer, "", "{}", exercise);
+            assert_eq!(app.state.mode, AppMode::Ready, "{}", exercise);
+
+            app.handle_input('k').unwrap();
+            let typed = if exercise == ExerciseMode::Reverse { "k" } else { "" };
+            assert_eq!(app.state.input_buffer, typed, "{}", exercise);
+        }
+    }
+
+    fn render_start_screen(width: u16, height: u16) -> String {
+        let mut app = App::new();
+        app.set_seed(3);
+        app.set_instant(true);
+        app.handle_enter().unwrap();
+        for _ in 0..5 {
+            let kana = app.state.current_kana.clone().unwrap();
+            for c in lookup_entry(&kana).unwrap().romaji(app.state.romanization).chars() {
+                app.handle_input(c).unwrap();
+            }
+        }
+        app.end_session();
+        app.state.mode = AppMode::Initial;
+
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(width, height)).unwrap();
+        terminal.draw(|f| app.render(f)).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..height)
+            .map(|y| (0..width).map(|x| buffer[(x, y)].symbol()).collect::<String>() + "\n")
+            .collect()
+    }
+
+    #[test]
+    fn start_screen_fits_goal_and_chart_at_80x24() {
+        let screen = render_start_screen(80, 24);
+        assert!(screen.contains("Streak: "), "{}", screen);
+        assert!(screen.contains("Response Time Trend"), "{}", screen);
+        assert!(!screen.contains("Mon"), "{}", screen);
+
+        let screen = render_start_screen(80, 60);
+        assert!(screen.contains("Mon"), "{}", screen);
+        assert!(screen.contains("Response Time Trend"), "{}", screen);
+    }
+
+    #[test]
+    fn repeated_kana_in_a_word_keep_separate_attempts() {
+        static KOKO: Word = Word { kana: "ここ", meaning: "here" };
+        let mut app = App::new();
+        app.set_exercise(ExerciseMode::Words);
+        app.state.current_word = Some(&KOKO);
+        let now = Utc::now();
+        assert!(app.record_word_attempt("koko", 1200.0, false, now));
+
+        let attempts = &app.state.history.character_stats["こ"].test_history;
+        assert_eq!(attempts.len(), 2);
+        assert!(attempts[0].start_time < attempts[1].start_time);
+        assert!(attempts[1].start_time < now);
+
+        let mut merged = UserHistory::default();
+        merged.merge(&app.state.history);
+        assert_eq!(merged.character_stats["こ"].test_history.len(), 2);
+    }
+
+    #[test]
+    fn due_only_words_pause_when_no_kana_is_due() {
+        let mut app = App::new();
+        app.set_exercise(ExerciseMode::Words);
+        app.set_scheduler(SchedulerKind::Weighted, true);
+        let later = Utc::now() + chrono::Duration::days(1);
+        for entry in &ALL_HIRAGANA {
+            let mut stats = CharacterStats::new();
+            stats.due = Some(later);
+            app.state.history.character_stats.insert(entry.kana.to_string(), stats);
+        }
+        app.handle_enter().unwrap();
+        assert_eq!(app.state.mode, AppMode::Paused);
+        assert!(app.state.current_word.is_none());
+
+        app.state.history.character_stats.get_mut("ね").unwrap().due = None;
+        app.handle_enter().unwrap();
+        assert_eq!(app.state.mode, AppMode::Ready);
+        assert!(app.state.current_word.is_some_and(|word| word.kana.contains('ね')));
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_kana_sequence() {
+        let sequence = |seed: u64| {
+            let mut app = App::new();
+            app.set_clock(Box::new(crate::clock::FixedClock::new(Utc::now())));
+            app.set_seed(seed);
+            app.handle_enter().unwrap();
+            let mut seen = Vec::new();
+            for _ in 0..30 {
+                let kana = app.state.current_kana.clone().unwrap();
+                let answer = lookup_entry(&kana).unwrap().romaji(app.state.romanization);
+                app.state.input_buffer = answer.to_string();
+                app.handle_enter().unwrap();
+                app.handle_enter().unwrap();
+                seen.push(kana);
+            }
+            seen
+        };
+        assert_eq!(sequence(42), sequence(42));
+        assert_ne!(sequence(42), sequence(43));
+    }
+
+    #[test]
+    fn retry_after_reveal_counts_as_hinted() {
+        let mut app = App::new();
+        app.set_seed(5);
+        app.handle_enter().unwrap();
+        let kana = app.state.current_kana.clone().unwrap();
+        let answer = app.state.expected_romaji.clone().unwrap();
+
+        app.state.input_buffer = "qqq".to_string();
+        app.handle_enter().unwrap();
+        app.reveal_answer();
+        app.handle_enter().unwrap();
+        app.state.input_buffer = answer.clone();
+        assert!(app.check_answer().unwrap());
+
+        let stats = &app.state.history.character_stats[&kana];
+        let retry = stats.test_history.last().unwrap();
+        assert!(retry.hints as usize >= answer.len());
+        assert_eq!(retry.accuracy_score(), 0.0);
+        assert_eq!(stats.repetitions, 0);
+        assert!(stats.is_due(Utc::now()));
+    }
+
+    #[test]
+    fn reverse_grid_leaves_out_homophones() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for kana_set in [&DAKUTEN_HIRAGANA[..], &ALL_KATAKANA[..]] {
+            for answer_idx in 0..kana_set.len() {
+                let choices = App::build_kana_choices(kana_set, answer_idx, &mut rng);
+                let entries: Vec<_> = choices.iter().filter_map(|kana| lookup_entry(kana)).collect();
+                assert_eq!(entries.len(), REVERSE_CHOICES);
+                for (idx, entry) in entries.iter().enumerate() {
+                    assert!(
+                        entries[idx + 1..].iter().all(|other| !entry.is_homophone(other)),
+                        "homophones in {:?}",
+                        choices
+                    );
+                }
+            }
+        }
+    }
+}
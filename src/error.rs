@@ -8,11 +8,27 @@ pub enum KanaError {
     #[error("Failed to serialize/deserialize data: {0}")]
     Serialization(#[from] serde_json::Error),
 
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
+
     #[error("Terminal error: {0}")]
     Terminal(String),
 
     #[error("Invalid input: {0}")]
     InvalidInput(String),
+
+    #[error("History migration failed: {0}")]
+    Migration(String),
+
+    #[error("{path} was written by a newer version (schema {found}, this build supports {supported}); refusing to modify it")]
+    NewerSchema {
+        path: String,
+        found: u32,
+        supported: u32,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, KanaError>;
\ No newline at end of file
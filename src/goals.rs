@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::types::{DailyGoal, SessionRecord};
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Style};
+use ratatui::widgets::Widget;
+use serde::Serialize;
+
+// Day labels on the left, then two columns per week
+const LABEL_WIDTH: u16 = 4;
+const CELL_WIDTH: u16 = 2;
+/// Rows the calendar needs: month names, then Monday to Sunday.
+pub const CALENDAR_HEIGHT: u16 = 8;
+pub const LEGEND: &str = "less · ░ ▒ ▓ █ more, █ = goal met";
+
+/// What one day of practice added up to.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DayTotals {
+    pub correct: u32,
+    pub seconds: f64,
+}
+
+/// Totals per local calendar day. A session counts towards the day it started.
+pub fn daily_totals(sessions: &[SessionRecord]) -> BTreeMap<NaiveDate, DayTotals> {
+    let mut days: BTreeMap<NaiveDate, DayTotals> = BTreeMap::new();
+    for session in sessions {
+        let day = days.entry(session.start.with_timezone(&Local).date_naive()).or_default();
+        day.correct += session.successes;
+        day.seconds += session.active_seconds;
+    }
+    days
+}
+
+impl DailyGoal {
+    /// How much of the goal a day reached; 1.0 or more means it was met.
+    pub fn progress(&self, day: &DayTotals) -> f64 {
+        match *self {
+            DailyGoal::Correct(count) => day.correct as f64 / count.max(1) as f64,
+            DailyGoal::Minutes(minutes) => day.seconds / 60.0 / minutes.max(1) as f64,
+        }
+    }
+
+    pub fn met(&self, day: &DayTotals) -> bool {
+        self.progress(day) >= 1.0
+    }
+
+    /// E.g. "12/20 correct answers".
+    pub fn status(&self, day: &DayTotals) -> String {
+        match *self {
+            DailyGoal::Correct(count) => format!("{}/{} correct answers", day.correct, count),
+            DailyGoal::Minutes(minutes) => format!("{}/{} minutes", (day.seconds / 60.0).floor(), minutes),
+        }
+    }
+}
+
+/// Consecutive days on which the goal was met.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Streak {
+    /// Up to today, or up to yesterday while today's goal is still open.
+    pub current: u32,
+    pub best: u32,
+    pub met_today: bool,
+}
+
+impl fmt::Display for Streak {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plural = if self.current == 1 { "" } else { "s" };
+        write!(f, "{} day{} (best {})", self.current, plural, self.best)
+    }
+}
+
+pub fn streak(days: &BTreeMap<NaiveDate, DayTotals>, goal: DailyGoal, today: NaiveDate) -> Streak {
+    let met = |date: NaiveDate| days.get(&date).is_some_and(|day| goal.met(day));
+
+    let mut best = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for (&date, _) in days.range(..=today).filter(|(_, day)| goal.met(day)) {
+        run = if previous == Some(date - Duration::days(1)) { run + 1 } else { 1 };
+        previous = Some(date);
+        best = best.max(run);
+    }
+
+    let met_today = met(today);
+    let mut current = 0;
+    let mut date = if met_today { today } else { today - Duration::days(1) };
+    while met(date) {
+        current += 1;
+        date -= Duration::days(1);
+    }
+
+    Streak { current, best, met_today }
+}
+
+/// A GitHub-style calendar: one column per week, Monday at the top, shaded by
+/// how much of the daily goal each day reached. Shows as many weeks up to
+/// `today` as fit.
+pub struct Calendar<'a> {
+    days: &'a BTreeMap<NaiveDate, DayTotals>,
+    goal: DailyGoal,
+    today: NaiveDate,
+}
+
+impl<'a> Calendar<'a> {
+    pub fn new(days: &'a BTreeMap<NaiveDate, DayTotals>, goal: DailyGoal, today: NaiveDate) -> Self {
+        Self { days, goal, today }
+    }
+
+    /// Width needed to show `weeks` weeks.
+    pub fn width(weeks: u16) -> u16 {
+        LABEL_WIDTH + weeks * CELL_WIDTH
+    }
+
+    fn cell(&self, date: NaiveDate) -> (&'static str, Color) {
+        let Some(day) = self.days.get(&date) else {
+            return ("·", Color::DarkGray);
+        };
+        match self.goal.progress(day) {
+            progress if progress >= 1.0 => ("█", Color::Rgb(57, 211, 83)),
+            progress if progress >= 2.0 / 3.0 => ("▓", Color::Rgb(38, 166, 65)),
+            progress if progress >= 1.0 / 3.0 => ("▒", Color::Rgb(0, 109, 50)),
+            _ => ("░", Color::Rgb(14, 68, 41)),
+        }
+    }
+
+    /// Renders into plain text lines, for output that isn't a terminal UI.
+    pub fn to_lines(&self, weeks: u16) -> Vec<String> {
+        let area = Rect::new(0, 0, Self::width(weeks), CALENDAR_HEIGHT);
+        let mut buf = Buffer::empty(area);
+        self.render_ref(area, &mut buf);
+        (0..area.height)
+            .map(|y| {
+                let line: String = (0..area.width).map(|x| buf[(x, y)].symbol()).collect();
+                line.trim_end().to_string()
+            })
+            .collect()
+    }
+
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        if area.height < CALENDAR_HEIGHT || area.width < Self::width(1) {
+            return;
+        }
+        let weeks = (area.width - LABEL_WIDTH) / CELL_WIDTH;
+        let label_style = Style::default().fg(Color::Gray);
+        for (row, label) in [(0, "Mon"), (2, "Wed"), (4, "Fri")] {
+            buf.set_string(area.x, area.y + 1 + row, label, label_style);
+        }
+
+        let this_monday = self.today - Duration::days(self.today.weekday().num_days_from_monday() as i64);
+        let first_monday = this_monday - Duration::weeks(weeks as i64 - 1);
+        // Month names are wider than a week, so skip one that would overlap the last
+        let mut free_from = 0;
+        for week in 0..weeks {
+            let monday = first_monday + Duration::weeks(week as i64);
+            let x = area.x + LABEL_WIDTH + week * CELL_WIDTH;
+            let new_month = week == 0 || monday.month() != (monday - Duration::weeks(1)).month();
+            if new_month && x >= free_from {
+                let name = monday.format("%b").to_string();
+                buf.set_string(x, area.y, &name, label_style);
+                free_from = x + name.len() as u16 + 1;
+            }
+
+            for row in 0..7 {
+                let date = monday + Duration::days(row as i64);
+                if date > self.today {
+                    break;
+                }
+                let (symbol, color) = self.cell(date);
+                buf.set_string(x, area.y + 1 + row, symbol, Style::default().fg(color));
+            }
+        }
+    }
+}
+
+impl Widget for Calendar<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        self.render_ref(area, buf);
+    }
+}
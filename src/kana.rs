@@ -1,98 +1,294 @@
+use crate::types::{KanaType, PracticeMode, Romanization};
+
+/// A kana together with its readings in each romanization system.
+///
+/// `hepburn` is the reading shown to the user. `wapuro` lists extra spellings
+/// that Japanese IMEs accept but no formal system uses (e.g. "nn" for ん).
+/// `hepburn_alt` lists readings also accepted under Hepburn, such as "di"
+/// for ぢ, which earlier versions expected.
+/// `mnemonic` is a memory aid for the shape, shown after mistakes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KanaEntry {
+    pub kana: &'static str,
+    pub hepburn: &'static str,
+    pub kunrei: &'static str,
+    pub nihon: &'static str,
+    pub wapuro: &'static [&'static str],
+    pub hepburn_alt: &'static [&'static str],
+    pub mnemonic: Option<&'static str>,
+}
+
+/// Builds an entry whose reading is the same in every system.
+const fn k(kana: &'static str, romaji: &'static str) -> KanaEntry {
+    KanaEntry {
+        kana,
+        hepburn: romaji,
+        kunrei: romaji,
+        nihon: romaji,
+        wapuro: &[],
+        hepburn_alt: &[],
+        mnemonic: None,
+    }
+}
+
+impl KanaEntry {
+    /// Sets the Kunrei-shiki reading. Nihon-shiki follows it unless overridden.
+    const fn kunrei(mut self, romaji: &'static str) -> Self {
+        self.kunrei = romaji;
+        self.nihon = romaji;
+        self
+    }
+
+    const fn nihon(mut self, romaji: &'static str) -> Self {
+        self.nihon = romaji;
+        self
+    }
+
+    const fn wapuro(mut self, romaji: &'static [&'static str]) -> Self {
+        self.wapuro = romaji;
+        self
+    }
+
+    const fn hepburn_alt(mut self, romaji: &'static [&'static str]) -> Self {
+        self.hepburn_alt = romaji;
+        self
+    }
+
+    const fn mnemonic(mut self, text: &'static str) -> Self {
+        self.mnemonic = Some(text);
+        self
+    }
+
+    /// The canonical reading for the given policy, used for display.
+    pub fn romaji(&self, policy: Romanization) -> &'static str {
+        match policy {
+            Romanization::Hepburn | Romanization::Any => self.hepburn,
+            Romanization::Kunrei => self.kunrei,
+            Romanization::Nihon => self.nihon,
+        }
+    }
+
+    /// All readings accepted under the given policy, canonical reading first.
+    pub fn readings(&self, policy: Romanization) -> Vec<&'static str> {
+        let mut readings = vec![self.romaji(policy)];
+        if policy == Romanization::Hepburn {
+            readings.extend(self.hepburn_alt);
+        }
+        if policy == Romanization::Any {
+            for &romaji in [self.kunrei, self.nihon].iter().chain(self.wapuro).chain(self.hepburn_alt) {
+                if !readings.contains(&romaji) {
+                    readings.push(romaji);
+                }
+            }
+        }
+        readings
+    }
+
+    /// Whether both kana share a reading in any romanization, like じ and ぢ.
+    pub fn is_homophone(&self, other: &KanaEntry) -> bool {
+        let readings = self.readings(Romanization::Any);
+        other
+            .readings(Romanization::Any)
+            .iter()
+            .any(|romaji| readings.contains(romaji))
+    }
+
+    pub fn accepts(&self, input: &str, policy: Romanization) -> bool {
+        self.readings(policy)
+            .iter()
+            .any(|romaji| romaji.eq_ignore_ascii_case(input))
+    }
+}
+
 // Hiragana constants
-pub const MAIN_HIRAGANA: [(&str, &str); 46] = [
-    ("あ", "a"),  ("い", "i"),   ("う", "u"),   ("え", "e"),  ("お", "o"),
-    ("か", "ka"), ("き", "ki"),  ("く", "ku"),  ("け", "ke"), ("こ", "ko"),
-    ("さ", "sa"), ("し", "shi"), ("す", "su"),  ("せ", "se"), ("そ", "so"),
-    ("た", "ta"), ("ち", "chi"), ("つ", "tsu"), ("て", "te"), ("と", "to"),
-    ("な", "na"), ("に", "ni"),  ("ぬ", "nu"),  ("ね", "ne"), ("の", "no"),
-    ("は", "ha"), ("ひ", "hi"),  ("ふ", "fu"),  ("へ", "he"), ("ほ", "ho"),
-    ("ま", "ma"), ("み", "mi"),  ("む", "mu"),  ("め", "me"), ("も", "mo"),
-    ("や", "ya"), ("ゆ", "yu"),  ("よ", "yo"),
-    ("ら", "ra"), ("り", "ri"),  ("る", "ru"),  ("れ", "re"), ("ろ", "ro"),
-    ("わ", "wa"), ("を", "wo"),  ("ん", "n"),
+pub const MAIN_HIRAGANA: [KanaEntry; 46] = [
+    k("あ", "a").mnemonic("A capital A with a loop swinging off its leg"),
+    k("い", "i").mnemonic("Two eels swimming side by side: ii"),
+    k("う", "u").mnemonic("Someone doubled over after a punch: uu!"),
+    k("え", "e").mnemonic("An exotic bird with a feather on its head"),
+    k("お", "o").mnemonic("Like あ with a ball flying off: oh!"),
+    k("か", "ka").mnemonic("A fist doing karate, with a shout on the side: ka!"),
+    k("き", "ki").mnemonic("A key with two teeth"),
+    k("く", "ku").mnemonic("A bird's open beak going coo"),
+    k("け", "ke").mnemonic("A keg standing next to a post"),
+    k("こ", "ko").mnemonic("Two coiled worms cohabiting"),
+    k("さ", "sa").mnemonic("A signpost leaning over a curve: sa"),
+    k("し", "shi").kunrei("si").mnemonic("A fishing hook: she fishes"),
+    k("す", "su").mnemonic("A swing looping around its bar"),
+    k("せ", "se").mnemonic("A mouth with a big fang says se"),
+    k("そ", "so").mnemonic("A zigzag thread being sewn"),
+    k("た", "ta").mnemonic("The letters t and a written side by side"),
+    k("ち", "chi").kunrei("ti").mnemonic("A cheerleader's chin with a pompom"),
+    k("つ", "tsu").kunrei("tu").mnemonic("A tsunami wave rolling over"),
+    k("て", "te").mnemonic("A hand (te) stretched out to the side"),
+    k("と", "to").mnemonic("A toe with a splinter stuck in it"),
+    k("な", "na").mnemonic("A nun praying beside a cross"),
+    k("に", "ni").mnemonic("A knee next to a walking stick"),
+    k("ぬ", "nu").mnemonic("ぬ has a loop — noodle"),
+    k("ね", "ne").mnemonic("A cat (neko) curling its tail"),
+    k("の", "no").mnemonic("A no-entry sign: a circle with a slash"),
+    k("は", "ha").mnemonic("An H next to an a: ha!"),
+    k("ひ", "hi").mnemonic("A big grin going hee hee"),
+    k("ふ", "fu").kunrei("hu").mnemonic("Mount Fuji with its two side slopes"),
+    k("へ", "he").mnemonic("A gentle hill: head up it"),
+    k("ほ", "ho").mnemonic("Like は wearing Santa's hat: ho ho ho"),
+    k("ま", "ma").mnemonic("A mast with a ribbon tied round it"),
+    k("み", "mi").mnemonic("Looks like the number 21: me at twenty-one"),
+    k("む", "mu").mnemonic("A cow with a swishing tail going moo"),
+    k("め", "me").mnemonic("め has no loop — an eye (me)"),
+    k("も", "mo").mnemonic("A fish hook with more bait on it"),
+    k("や", "ya").mnemonic("A yak with a horn sticking out"),
+    k("ゆ", "yu").mnemonic("A fish swimming through a U"),
+    k("よ", "yo").mnemonic("A yo-yo dangling from a finger"),
+    k("ら", "ra").mnemonic("A rabbit with one ear up"),
+    k("り", "ri").mnemonic("Two reeds bending by the river"),
+    k("る", "ru").mnemonic("A route that ends in a loop"),
+    k("れ", "re").mnemonic("A man kneeling in respect, legs straight out"),
+    k("ろ", "ro").mnemonic("A road with no loop at the end (unlike る)"),
+    k("わ", "wa").mnemonic("A wave curling at the end (unlike れ)"),
+    k("を", "wo").kunrei("o").nihon("wo").mnemonic("A man kicking and shouting whoa"),
+    k("ん", "n").wapuro(&["nn", "n'"]).mnemonic("A cursive lowercase n"),
 ];
 
-pub const DAKUTEN_HIRAGANA: [(&str, &str); 25] = [
-    ("が", "ga"), ("ぎ", "gi"), ("ぐ", "gu"), ("げ", "ge"), ("ご", "go"),
-    ("ざ", "za"), ("じ", "ji"), ("ず", "zu"), ("ぜ", "ze"), ("ぞ", "zo"),
-    ("だ", "da"), ("ぢ", "di"), ("づ", "du"), ("で", "de"), ("ど", "do"),
-    ("ば", "ba"), ("び", "bi"), ("ぶ", "bu"), ("べ", "be"), ("ぼ", "bo"),
-    ("ぱ", "pa"), ("ぴ", "pi"), ("ぷ", "pu"), ("ぺ", "pe"), ("ぽ", "po"),
+pub const DAKUTEN_HIRAGANA: [KanaEntry; 25] = [
+    k("が", "ga"), k("ぎ", "gi"), k("ぐ", "gu"), k("げ", "ge"), k("ご", "go"),
+    k("ざ", "za"), k("じ", "ji").kunrei("zi"), k("ず", "zu"), k("ぜ", "ze"), k("ぞ", "zo"),
+    k("だ", "da"), k("ぢ", "ji").kunrei("zi").nihon("di").hepburn_alt(&["di"]), k("づ", "zu").nihon("du").hepburn_alt(&["du"]), k("で", "de"), k("ど", "do"),
+    k("ば", "ba"), k("び", "bi"), k("ぶ", "bu"), k("べ", "be"), k("ぼ", "bo"),
+    k("ぱ", "pa"), k("ぴ", "pi"), k("ぷ", "pu"), k("ぺ", "pe"), k("ぽ", "po"),
 ];
 
-pub const COMBINATION_HIRAGANA: [(&str, &str); 33] = [
-    ("きゃ", "kya"), ("きゅ", "kyu"), ("きょ", "kyo"),
-    ("しゃ", "sha"), ("しゅ", "shu"), ("しょ", "sho"),
-    ("ちゃ", "cha"), ("ちゅ", "chu"), ("ちょ", "cho"),
-    ("にゃ", "nya"), ("にゅ", "nyu"), ("にょ", "nyo"),
-    ("ひゃ", "hya"), ("ひゅ", "hyu"), ("ひょ", "hyo"),
-    ("みゃ", "mya"), ("みゅ", "myu"), ("みょ", "myo"),
-    ("りゃ", "rya"), ("りゅ", "ryu"), ("りょ", "ryo"),
-    ("ぎゃ", "gya"), ("ぎゅ", "gyu"), ("ぎょ", "gyo"),
-    ("じゃ", "ja"),  ("じゅ", "ju"),  ("じょ", "jo"),
-    ("びゃ", "bya"), ("びゅ", "byu"), ("びょ", "byo"),
-    ("ぴゃ", "pya"), ("ぴゅ", "pyu"), ("ぴょ", "pyo"),
+pub const COMBINATION_HIRAGANA: [KanaEntry; 33] = [
+    k("きゃ", "kya"), k("きゅ", "kyu"), k("きょ", "kyo"),
+    k("しゃ", "sha").kunrei("sya"), k("しゅ", "shu").kunrei("syu"), k("しょ", "sho").kunrei("syo"),
+    k("ちゃ", "cha").kunrei("tya").wapuro(&["cya"]), k("ちゅ", "chu").kunrei("tyu").wapuro(&["cyu"]), k("ちょ", "cho").kunrei("tyo").wapuro(&["cyo"]),
+    k("にゃ", "nya"), k("にゅ", "nyu"), k("にょ", "nyo"),
+    k("ひゃ", "hya"), k("ひゅ", "hyu"), k("ひょ", "hyo"),
+    k("みゃ", "mya"), k("みゅ", "myu"), k("みょ", "myo"),
+    k("りゃ", "rya"), k("りゅ", "ryu"), k("りょ", "ryo"),
+    k("ぎゃ", "gya"), k("ぎゅ", "gyu"), k("ぎょ", "gyo"),
+    k("じゃ", "ja").kunrei("zya").wapuro(&["jya"]),  k("じゅ", "ju").kunrei("zyu").wapuro(&["jyu"]),  k("じょ", "jo").kunrei("zyo").wapuro(&["jyo"]),
+    k("びゃ", "bya"), k("びゅ", "byu"), k("びょ", "byo"),
+    k("ぴゃ", "pya"), k("ぴゅ", "pyu"), k("ぴょ", "pyo"),
 ];
 
 // Katakana constants
-pub const MAIN_KATAKANA: [(&str, &str); 46] = [
-    ("ア", "a"),  ("イ", "i"),   ("ウ", "u"),   ("エ", "e"),  ("オ", "o"),
-    ("カ", "ka"), ("キ", "ki"),  ("ク", "ku"),  ("ケ", "ke"), ("コ", "ko"),
-    ("サ", "sa"), ("シ", "shi"), ("ス", "su"),  ("セ", "se"), ("ソ", "so"),
-    ("タ", "ta"), ("チ", "chi"), ("ツ", "tsu"), ("テ", "te"), ("ト", "to"),
-    ("ナ", "na"), ("ニ", "ni"),  ("ヌ", "nu"),  ("ネ", "ne"), ("ノ", "no"),
-    ("ハ", "ha"), ("ヒ", "hi"),  ("フ", "fu"),  ("ヘ", "he"), ("ホ", "ho"),
-    ("マ", "ma"), ("ミ", "mi"),  ("ム", "mu"),  ("メ", "me"), ("モ", "mo"),
-    ("ヤ", "ya"), ("ユ", "yu"),  ("ヨ", "yo"),
-    ("ラ", "ra"), ("リ", "ri"),  ("ル", "ru"),  ("レ", "re"), ("ロ", "ro"),
-    ("ワ", "wa"), ("ヲ", "wo"),  ("ン", "n"),
+pub const MAIN_KATAKANA: [KanaEntry; 46] = [
+    k("ア", "a").mnemonic("An axe with a curved blade"),
+    k("イ", "i").mnemonic("An eagle leaning into a dive"),
+    k("ウ", "u").mnemonic("う with a roof on top"),
+    k("エ", "e").mnemonic("An elevator shaft, floor to ceiling"),
+    k("オ", "o").mnemonic("An opera singer with arms spread"),
+    k("カ", "ka").mnemonic("か without the dash"),
+    k("キ", "ki").mnemonic("A key with two teeth, straightened"),
+    k("ク", "ku").mnemonic("A cook's hat seen from the side"),
+    k("ケ", "ke").mnemonic("A K knocked over"),
+    k("コ", "ko").mnemonic("A corner of a room"),
+    k("サ", "sa").mnemonic("A saddle on a fence"),
+    k("シ", "shi").kunrei("si").mnemonic("She smiles upwards: dashes stacked, stroke rises (unlike ツ)"),
+    k("ス", "su").mnemonic("A sumo wrestler mid-stance"),
+    k("セ", "se").mnemonic("せ with a stroke missing"),
+    k("ソ", "so").mnemonic("A needle sewing downwards (unlike ン)"),
+    k("タ", "ta").mnemonic("ク with an extra tack inside"),
+    k("チ", "chi").kunrei("ti").mnemonic("A cheerleader with arms out"),
+    k("ツ", "tsu").kunrei("tu").mnemonic("A tsunami falling down: dashes side by side (unlike シ)"),
+    k("テ", "te").mnemonic("A telephone pole with its wires"),
+    k("ト", "to").mnemonic("A totem pole with a branch"),
+    k("ナ", "na").mnemonic("A knife's cross guard"),
+    k("ニ", "ni").mnemonic("Two lines: ni means two"),
+    k("ヌ", "nu").mnemonic("Noodles picked up with chopsticks"),
+    k("ネ", "ne").mnemonic("A necklace with a pendant"),
+    k("ノ", "no").mnemonic("A single slash saying no"),
+    k("ハ", "ha").mnemonic("Two lines opening like a laugh: ha ha"),
+    k("ヒ", "hi").mnemonic("A heel kicking out to the left"),
+    k("フ", "fu").kunrei("hu").mnemonic("One slope of Mount Fuji"),
+    k("ヘ", "he").mnemonic("Same hill as へ"),
+    k("ホ", "ho").mnemonic("A holy cross with two candles"),
+    k("マ", "ma").mnemonic("A mama's apron"),
+    k("ミ", "mi").mnemonic("Three lines: mi as in mittsu, three"),
+    k("ム", "mu").mnemonic("A cow's nose going moo"),
+    k("メ", "me").mnemonic("A messy X marks the spot"),
+    k("モ", "mo").mnemonic("も with more lines and no hook"),
+    k("ヤ", "ya").mnemonic("A yak's horn, straightened"),
+    k("ユ", "yu").mnemonic("A U-turn on its side"),
+    k("ヨ", "yo").mnemonic("Three bars of a yo-yo ladder"),
+    k("ラ", "ra").mnemonic("A rabbit with a flat ear"),
+    k("リ", "ri").mnemonic("Same reeds as り"),
+    k("ル", "ru").mnemonic("Two roots growing down"),
+    k("レ", "re").mnemonic("A leg with the foot kicked up"),
+    k("ロ", "ro").mnemonic("A road sign: a plain box"),
+    k("ワ", "wa").mnemonic("A wine glass seen from the side"),
+    k("ヲ", "wo").kunrei("o").nihon("wo").mnemonic("ワ with an extra whoa stroke"),
+    k("ン", "n").wapuro(&["nn", "n'"]).mnemonic("A needle pointing up (unlike ソ)"),
 ];
 
-pub const DAKUTEN_KATAKANA: [(&str, &str); 26] = [
-    ("ガ", "ga"), ("ギ", "gi"), ("グ", "gu"), ("ゲ", "ge"), ("ゴ", "go"),
-    ("ザ", "za"), ("ジ", "ji"), ("ズ", "zu"), ("ゼ", "ze"), ("ゾ", "zo"),
-    ("ダ", "da"), ("ヂ", "ji"), ("ヅ", "zu"), ("デ", "de"), ("ド", "do"),
-    ("バ", "ba"), ("ビ", "bi"), ("ブ", "bu"), ("ベ", "be"), ("ボ", "bo"),
-    ("パ", "pa"), ("ピ", "pi"), ("プ", "pu"), ("ペ", "pe"), ("ポ", "po"),
-    ("ヴ", "vu"),  // V-sound
+pub const DAKUTEN_KATAKANA: [KanaEntry; 26] = [
+    k("ガ", "ga"), k("ギ", "gi"), k("グ", "gu"), k("ゲ", "ge"), k("ゴ", "go"),
+    k("ザ", "za"), k("ジ", "ji").kunrei("zi"), k("ズ", "zu"), k("ゼ", "ze"), k("ゾ", "zo"),
+    k("ダ", "da"), k("ヂ", "ji").kunrei("zi").nihon("di").hepburn_alt(&["di"]), k("ヅ", "zu").nihon("du").hepburn_alt(&["du"]), k("デ", "de"), k("ド", "do"),
+    k("バ", "ba"), k("ビ", "bi"), k("ブ", "bu"), k("ベ", "be"), k("ボ", "bo"),
+    k("パ", "pa"), k("ピ", "pi"), k("プ", "pu"), k("ペ", "pe"), k("ポ", "po"),
+    k("ヴ", "vu"),  // V-sound
 ];
 
-pub const COMBINATION_KATAKANA: [(&str, &str); 55] = [
+pub const COMBINATION_KATAKANA: [KanaEntry; 55] = [
     // Standard y-combinations
-    ("キャ", "kya"), ("キュ", "kyu"), ("キョ", "kyo"),
-    ("シャ", "sha"), ("シュ", "shu"), ("ショ", "sho"),
-    ("チャ", "cha"), ("チュ", "chu"), ("チョ", "cho"),
-    ("ニャ", "nya"), ("ニュ", "nyu"), ("ニョ", "nyo"),
-    ("ヒャ", "hya"), ("ヒュ", "hyu"), ("ヒョ", "hyo"),
-    ("ミャ", "mya"), ("ミュ", "myu"), ("ミョ", "myo"),
-    ("リャ", "rya"), ("リュ", "ryu"), ("リョ", "ryo"),
-    ("ギャ", "gya"), ("ギュ", "gyu"), ("ギョ", "gyo"),
-    ("ジャ", "ja"),  ("ジュ", "ju"),  ("ジョ", "jo"),
-    ("ヂャ", "dya"), ("ヂュ", "dyu"), ("ヂョ", "dyo"),
-    ("ビャ", "bya"), ("ビュ", "byu"), ("ビョ", "byo"),
-    ("ピャ", "pya"), ("ピュ", "pyu"), ("ピョ", "pyo"),
+    k("キャ", "kya"), k("キュ", "kyu"), k("キョ", "kyo"),
+    k("シャ", "sha").kunrei("sya"), k("シュ", "shu").kunrei("syu"), k("ショ", "sho").kunrei("syo"),
+    k("チャ", "cha").kunrei("tya").wapuro(&["cya"]), k("チュ", "chu").kunrei("tyu").wapuro(&["cyu"]), k("チョ", "cho").kunrei("tyo").wapuro(&["cyo"]),
+    k("ニャ", "nya"), k("ニュ", "nyu"), k("ニョ", "nyo"),
+    k("ヒャ", "hya"), k("ヒュ", "hyu"), k("ヒョ", "hyo"),
+    k("ミャ", "mya"), k("ミュ", "myu"), k("ミョ", "myo"),
+    k("リャ", "rya"), k("リュ", "ryu"), k("リョ", "ryo"),
+    k("ギャ", "gya"), k("ギュ", "gyu"), k("ギョ", "gyo"),
+    k("ジャ", "ja").kunrei("zya").wapuro(&["jya"]),  k("ジュ", "ju").kunrei("zyu").wapuro(&["jyu"]),  k("ジョ", "jo").kunrei("zyo").wapuro(&["jyo"]),
+    k("ヂャ", "ja").kunrei("zya").nihon("dya").hepburn_alt(&["dya"]), k("ヂュ", "ju").kunrei("zyu").nihon("dyu").hepburn_alt(&["dyu"]), k("ヂョ", "jo").kunrei("zyo").nihon("dyo").hepburn_alt(&["dyo"]),
+    k("ビャ", "bya"), k("ビュ", "byu"), k("ビョ", "byo"),
+    k("ピャ", "pya"), k("ピュ", "pyu"), k("ピョ", "pyo"),
     
     // Foreign sound combinations
-    ("ヴァ", "va"), ("ヴィ", "vi"), ("ヴェ", "ve"), ("ヴォ", "vo"),
-    ("ウィ", "wi"), ("ウェ", "we"), ("ウォ", "wo"),
-    ("ファ", "fa"), ("フィ", "fi"), ("フェ", "fe"), ("フォ", "fo"),
-    ("ツァ", "tsa"), ("ツィ", "tsi"), ("ツェ", "tse"), ("ツォ", "tso"),
+    k("ヴァ", "va"), k("ヴィ", "vi"), k("ヴェ", "ve"), k("ヴォ", "vo"),
+    k("ウィ", "wi"), k("ウェ", "we"), k("ウォ", "wo").wapuro(&["who"]),
+    k("ファ", "fa"), k("フィ", "fi"), k("フェ", "fe"), k("フォ", "fo"),
+    k("ツァ", "tsa"), k("ツィ", "tsi"), k("ツェ", "tse"), k("ツォ", "tso"),
     
     // Special combinations
-    ("シェ", "she"),
-    ("ジェ", "je"),
-    ("チェ", "che"),
+    k("シェ", "she").kunrei("sye"),
+    k("ジェ", "je").kunrei("zye").wapuro(&["jye"]),
+    k("チェ", "che").kunrei("tye").wapuro(&["cye"]),
     
     // Irregular combinations from the image
-    ("イェ", "ye")
+    k("イェ", "ye")
 ];
 
+// Groups of kana that learners commonly confuse by shape
+pub const SIMILAR_KANA: &[&[&str]] = &[
+    &["ぬ", "め", "あ", "お"], &["ね", "れ", "わ"], &["る", "ろ"], &["さ", "ち", "き"],
+    &["は", "ほ", "け"], &["い", "り", "こ"], &["う", "つ", "ら"], &["ま", "も"],
+    &["し", "つ", "く"], &["た", "な"], &["ん", "そ"], &["ゆ", "よ"],
+    &["シ", "ツ", "ン", "ソ"], &["ク", "ワ", "ウ", "フ", "ケ"], &["コ", "ユ", "ロ"],
+    &["ナ", "メ", "ヌ", "ス"], &["チ", "テ"], &["ハ", "ル"], &["マ", "ア"],
+    &["ヨ", "ヲ"], &["セ", "ヤ"], &["リ", "ソ"], &["ノ", "メ"],
+    &["ば", "ぱ"], &["び", "ぴ"], &["ぶ", "ぷ"], &["べ", "ぺ"], &["ぼ", "ぽ"],
+    &["バ", "パ"], &["ビ", "ピ"], &["ブ", "プ"], &["ベ", "ペ"], &["ボ", "ポ"],
+    &["じ", "ぢ"], &["ず", "づ"], &["ジ", "ヂ"], &["ズ", "ヅ"],
+];
+
+/// Kana that look like the given one, excluding itself.
+pub fn similar_kana(kana: &str) -> impl Iterator<Item = &'static str> + '_ {
+    SIMILAR_KANA
+        .iter()
+        .filter(move |group| group.contains(&kana))
+        .flat_map(|group| group.iter().copied())
+        .filter(move |&other| other != kana)
+}
+
 const fn make_all_kana_by_type(
-    main: [(&'static str, &'static str); 46],
-    dakuten: [(&'static str, &'static str); 25],
-    combination: [(&'static str, &'static str); 33],
-) -> [(&'static str, &'static str); 104] {
-    let mut result = [("", ""); 104];
+    main: [KanaEntry; 46],
+    dakuten: [KanaEntry; 25],
+    combination: [KanaEntry; 33],
+) -> [KanaEntry; 104] {
+    let mut result = [k("", ""); 104];
     let mut offset = 0;
     let mut i = 0;
 
@@ -120,8 +316,8 @@ const fn make_all_kana_by_type(
 
     result
 }
-const fn make_all_katakana() -> [(&'static str, &'static str); 127] {  // 46 + 26 + 55
-    let mut result = [("", ""); 127];
+const fn make_all_katakana() -> [KanaEntry; 127] {  // 46 + 26 + 55
+    let mut result = [k("", ""); 127];
     let mut offset = 0;
     let mut i = 0;
 
@@ -150,5 +346,27 @@ const fn make_all_katakana() -> [(&'static str, &'static str); 127] {  // 46 + 2
     result
 }
 
-pub const ALL_HIRAGANA: [(&str, &str); 104] = make_all_kana_by_type(MAIN_HIRAGANA, DAKUTEN_HIRAGANA, COMBINATION_HIRAGANA);
-pub const ALL_KATAKANA: [(&str, &str); 127] = make_all_katakana();
\ No newline at end of file
+pub const ALL_HIRAGANA: [KanaEntry; 104] = make_all_kana_by_type(MAIN_HIRAGANA, DAKUTEN_HIRAGANA, COMBINATION_HIRAGANA);
+pub const ALL_KATAKANA: [KanaEntry; 127] = make_all_katakana();
+
+/// The kana practised with the given type and practice set.
+pub fn kana_set(kana_type: KanaType, practice_mode: PracticeMode) -> &'static [KanaEntry] {
+    match (kana_type, practice_mode) {
+        (KanaType::Hiragana, PracticeMode::Main) => &MAIN_HIRAGANA,
+        (KanaType::Hiragana, PracticeMode::Dakuten) => &DAKUTEN_HIRAGANA,
+        (KanaType::Hiragana, PracticeMode::Combination) => &COMBINATION_HIRAGANA,
+        (KanaType::Hiragana, PracticeMode::All) => &ALL_HIRAGANA,
+        (KanaType::Katakana, PracticeMode::Main) => &MAIN_KATAKANA,
+        (KanaType::Katakana, PracticeMode::Dakuten) => &DAKUTEN_KATAKANA,
+        (KanaType::Katakana, PracticeMode::Combination) => &COMBINATION_KATAKANA,
+        (KanaType::Katakana, PracticeMode::All) => &ALL_KATAKANA,
+    }
+}
+
+/// Finds a kana in any practice set, hiragana first.
+pub fn lookup_entry(kana: &str) -> Option<&'static KanaEntry> {
+    ALL_HIRAGANA
+        .iter()
+        .chain(ALL_KATAKANA.iter())
+        .find(|entry| entry.kana == kana)
+}
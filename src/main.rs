@@ -1,34 +1,57 @@
+mod anki;
 mod app;
+mod clock;
 mod error;
+mod goals;
 mod kana;
+mod migrations;
+mod mnemonics;
+mod paths;
+mod profiles;
+mod report;
+mod romaji;
+mod scheduler;
+mod sqlite;
+mod storage;
+mod transfer;
 mod types;
+mod words;
 
-use app::App;
+use app::{App, DEFAULT_IDLE_AFTER, DEFAULT_REVEAL_AFTER};
+use chrono::{DateTime, Utc};
+use clock::FixedClock;
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use error::{KanaError, Result};
 use ratatui::{backend::CrosstermBackend, Terminal};
-use std::path::Path;
-use std::{
-    env,
-    fs::{File, OpenOptions},
-};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::{env, thread};
 use std::{
     io,
     time::{Duration, Instant},
 };
-use types::{AppMode, KanaType, PracticeMode, UserHistory};
+use profiles::{ProfileSession, DEFAULT_PROFILE};
+use report::{Report, ReportFilter, ReportFormat};
+use storage::Autosaver;
+use types::{AppMode, DailyGoal, ExerciseMode, KanaType, PracticeMode, Romanization, SchedulerKind, StorageKind};
 
 use tracing::{debug, error, info, warn};
 use tracing_appender::rolling::{RollingFileAppender, Rotation};
 use tracing_subscriber::{fmt::format::FmtSpan, EnvFilter};
 
-const HISTORY_FILE: &str = "kana_history.json";
 const VALID_PRACTICE_FLAGS: &[&str] = &["main", "dakuten", "combination", "all"];
 const VALID_KANA_FLAGS: &[&str] = &["hiragana", "katakana"];
+const VALID_ROMANIZATION_FLAGS: &[&str] = &["hepburn", "kunrei", "nihon", "any"];
+const VALID_EXERCISE_FLAGS: &[&str] = &["romaji", "reverse", "choice", "words"];
+const VALID_SCHEDULER_FLAGS: &[&str] = &["weighted", "sm2", "uniform", "deck", "weakest"];
+const VALID_STORAGE_FLAGS: &[&str] = &["json", "sqlite"];
+const VALID_FORMAT_FLAGS: &[&str] = &["table", "json", "csv"];
 
 // Mapping for short flags
 const VALID_SHORT_FLAGS: &[(&str, KanaType, PracticeType)] = &[
@@ -61,10 +84,53 @@ const VALID_COMBINED_FLAGS: &[(&str, KanaType, PracticeType)] = &[
     ("ka", KanaType::Katakana, PracticeType::All),
 ];
 
+/// Subcommands that run without starting the practice UI.
+#[derive(Debug, Clone, PartialEq)]
+enum Command {
+    Convert(StorageKind),
+    Profile(ProfileCommand),
+    Stats,
+    Export(PathBuf),
+    Import(PathBuf),
+    Merge(Vec<PathBuf>),
+    Anki(PathBuf),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum ProfileCommand {
+    List,
+    Rename { from: String, to: String },
+    Delete { name: String },
+    Merge { from: String, into: String },
+}
+
 #[derive(Debug)]
 struct CliArgs {
+    command: Option<Command>,
+    profile: Option<String>,
+    confirmed: bool,
+    format: ReportFormat,
+    daily: bool,
+    card_count: usize,
+    // Flags given on the command line override the profile's saved settings
     kana_type: KanaType,
+    kana_type_set: bool,
     practice_type: PracticeType,
+    practice_type_set: bool,
+    romanization: Option<Romanization>,
+    exercise: Option<ExerciseMode>,
+    scheduler: Option<SchedulerKind>,
+    due_only: bool,
+    instant: bool,
+    goal: Option<DailyGoal>,
+    seed: Option<u64>,
+    frozen_time: Option<DateTime<Utc>>,
+    data_dir: Option<PathBuf>,
+    storage: Option<StorageKind>,
+    autosave_answers: u32,
+    autosave_interval: Duration,
+    reveal_after: u32,
+    idle_after: Duration,
 }
 
 #[derive(Debug, PartialEq, Copy, Clone)]
@@ -78,14 +144,36 @@ enum PracticeType {
 impl Default for CliArgs {
     fn default() -> Self {
         Self {
+            command: None,
+            profile: None,
+            confirmed: false,
+            format: ReportFormat::default(),
+            daily: false,
+            card_count: anki::DEFAULT_CARD_COUNT,
             kana_type: KanaType::Hiragana,
+            kana_type_set: false,
             practice_type: PracticeType::Main,
+            practice_type_set: false,
+            romanization: None,
+            exercise: None,
+            scheduler: None,
+            due_only: false,
+            instant: false,
+            goal: None,
+            seed: None,
+            frozen_time: None,
+            data_dir: None,
+            storage: None,
+            autosave_answers: Autosaver::DEFAULT_EVERY_ANSWERS,
+            autosave_interval: Autosaver::DEFAULT_INTERVAL,
+            reveal_after: DEFAULT_REVEAL_AFTER,
+            idle_after: DEFAULT_IDLE_AFTER,
         }
     }
 }
 
 fn setup_logging() -> Result<()> {
-    let file_appender = RollingFileAppender::new(Rotation::DAILY, "logs", "kana_practice.log");
+    let file_appender = RollingFileAppender::new(Rotation::DAILY, paths::log_dir(), "kana_practice.log");
 
     let env_filter = if cfg!(debug_assertions) {
         EnvFilter::new("debug")
@@ -117,6 +205,7 @@ fn setup_logging() -> Result<()> {
     Ok(())
 }
 
+#[allow(clippy::needless_range_loop, clippy::clone_on_copy)]
 fn levenshtein_distance(a: &str, b: &str) -> usize {
     let a_len = a.chars().count();
     let b_len = b.chars().count();
@@ -222,6 +311,142 @@ fn parse_practice_type(arg: &str) -> Option<PracticeType> {
     None
 }
 
+fn parse_romanization(value: &str) -> Result<Romanization> {
+    let value = value.to_lowercase();
+
+    if let Some(matched_flag) = is_valid_prefix(&value, VALID_ROMANIZATION_FLAGS) {
+        return match matched_flag {
+            "hepburn" => Ok(Romanization::Hepburn),
+            "kunrei" => Ok(Romanization::Kunrei),
+            "nihon" => Ok(Romanization::Nihon),
+            _ => Ok(Romanization::Any),
+        };
+    }
+
+    match find_closest_match(&value, VALID_ROMANIZATION_FLAGS) {
+        Some(suggestion) => Err(KanaError::InvalidInput(format!(
+            "Unknown romanization '{}'. Did you mean '{}'?",
+            value, suggestion
+        ))),
+        None => Err(KanaError::InvalidInput(format!(
+            "Unknown romanization '{}'. Valid options are: {:?}",
+            value, VALID_ROMANIZATION_FLAGS
+        ))),
+    }
+}
+
+fn parse_exercise(value: &str) -> Result<ExerciseMode> {
+    let value = value.to_lowercase();
+
+    if let Some(matched_flag) = is_valid_prefix(&value, VALID_EXERCISE_FLAGS) {
+        return match matched_flag {
+            "reverse" => Ok(ExerciseMode::Reverse),
+            "choice" => Ok(ExerciseMode::Choice),
+            "words" => Ok(ExerciseMode::Words),
+            _ => Ok(ExerciseMode::Romaji),
+        };
+    }
+
+    match find_closest_match(&value, VALID_EXERCISE_FLAGS) {
+        Some(suggestion) => Err(KanaError::InvalidInput(format!(
+            "Unknown exercise '{}'. Did you mean '{}'?",
+            value, suggestion
+        ))),
+        None => Err(KanaError::InvalidInput(format!(
+            "Unknown exercise '{}'. Valid options are: {:?}",
+            value, VALID_EXERCISE_FLAGS
+        ))),
+    }
+}
+
+fn parse_scheduler(value: &str) -> Result<SchedulerKind> {
+    let value = value.to_lowercase();
+
+    if let Some(matched_flag) = is_valid_prefix(&value, VALID_SCHEDULER_FLAGS) {
+        return match matched_flag {
+            "sm2" => Ok(SchedulerKind::Sm2),
+            "uniform" => Ok(SchedulerKind::Uniform),
+            "deck" => Ok(SchedulerKind::Deck),
+            "weakest" => Ok(SchedulerKind::Weakest),
+            _ => Ok(SchedulerKind::Weighted),
+        };
+    }
+
+    match find_closest_match(&value, VALID_SCHEDULER_FLAGS) {
+        Some(suggestion) => Err(KanaError::InvalidInput(format!(
+            "Unknown scheduler '{}'. Did you mean '{}'?",
+            value, suggestion
+        ))),
+        None => Err(KanaError::InvalidInput(format!(
+            "Unknown scheduler '{}'. Valid options are: {:?}",
+            value, VALID_SCHEDULER_FLAGS
+        ))),
+    }
+}
+
+fn parse_storage(value: &str) -> Result<StorageKind> {
+    let value = value.to_lowercase();
+
+    if let Some(matched_flag) = is_valid_prefix(&value, VALID_STORAGE_FLAGS) {
+        return match matched_flag {
+            "sqlite" => Ok(StorageKind::Sqlite),
+            _ => Ok(StorageKind::Json),
+        };
+    }
+
+    match find_closest_match(&value, VALID_STORAGE_FLAGS) {
+        Some(suggestion) => Err(KanaError::InvalidInput(format!(
+            "Unknown storage '{}'. Did you mean '{}'?",
+            value, suggestion
+        ))),
+        None => Err(KanaError::InvalidInput(format!(
+            "Unknown storage '{}'. Valid options are: {:?}",
+            value, VALID_STORAGE_FLAGS
+        ))),
+    }
+}
+
+fn parse_format(value: &str) -> Result<ReportFormat> {
+    let value = value.to_lowercase();
+
+    if let Some(matched_flag) = is_valid_prefix(&value, VALID_FORMAT_FLAGS) {
+        return match matched_flag {
+            "json" => Ok(ReportFormat::Json),
+            "csv" => Ok(ReportFormat::Csv),
+            _ => Ok(ReportFormat::Table),
+        };
+    }
+
+    match find_closest_match(&value, VALID_FORMAT_FLAGS) {
+        Some(suggestion) => Err(KanaError::InvalidInput(format!(
+            "Unknown format '{}'. Did you mean '{}'?",
+            value, suggestion
+        ))),
+        None => Err(KanaError::InvalidInput(format!(
+            "Unknown format '{}'. Valid options are: {:?}",
+            value, VALID_FORMAT_FLAGS
+        ))),
+    }
+}
+
+/// Returns the value of `--name value` or `--name=value` if `arg` is that option.
+fn take_option_value(
+    arg: &str,
+    name: &str,
+    args: &mut impl Iterator<Item = String>,
+) -> Result<Option<String>> {
+    if arg == name {
+        return args
+            .next()
+            .map(Some)
+            .ok_or_else(|| KanaError::InvalidInput(format!("Missing value for '{}'", name)));
+    }
+    Ok(arg
+        .strip_prefix(name)
+        .and_then(|rest| rest.strip_prefix('='))
+        .map(str::to_string))
+}
+
 fn parse_single_short_flag(flag: &str) -> Option<(KanaType, PracticeType)> {
     VALID_SHORT_FLAGS
         .iter()
@@ -239,21 +464,209 @@ fn parse_combined_flags(flags: &str) -> Option<(KanaType, PracticeType)> {
 fn parse_args() -> Result<CliArgs> {
     let args: Vec<String> = env::args().skip(1).collect();
     let mut cli_args = CliArgs::default();
-    let mut practice_type_set = false;
-    let mut kana_type_set = false;
 
     if args.is_empty() {
         info!("No arguments provided, falling back to default settings: main hiragana. Available options:");
         info!("Practice types: {:?}", VALID_PRACTICE_FLAGS);
         info!("Kana types: {:?}", VALID_KANA_FLAGS);
+        info!("Romanization (--romanization): {:?}", VALID_ROMANIZATION_FLAGS);
+        info!("Exercises (--exercise): {:?}", VALID_EXERCISE_FLAGS);
+        info!("Schedulers (--scheduler): {:?}, plus --due-only", VALID_SCHEDULER_FLAGS);
         return Ok(cli_args);
     }
 
-    for arg in args {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
         if arg.is_empty() {
             continue;
         }
 
+        if let Some(value) = take_option_value(&arg, "--romanization", &mut args)? {
+            cli_args.romanization = Some(parse_romanization(&value)?);
+            continue;
+        }
+
+        if let Some(value) = take_option_value(&arg, "--exercise", &mut args)? {
+            cli_args.exercise = Some(parse_exercise(&value)?);
+            continue;
+        }
+
+        if let Some(value) = take_option_value(&arg, "--scheduler", &mut args)? {
+            cli_args.scheduler = Some(parse_scheduler(&value)?);
+            continue;
+        }
+
+        if let Some(value) = take_option_value(&arg, "--seed", &mut args)? {
+            cli_args.seed = Some(value.parse().map_err(|_| {
+                KanaError::InvalidInput(format!("Invalid seed '{}': expected a non-negative integer", value))
+            })?);
+            continue;
+        }
+
+        if let Some(value) = take_option_value(&arg, "--frozen-clock", &mut args)? {
+            let time = DateTime::parse_from_rfc3339(&value).map_err(|e| {
+                KanaError::InvalidInput(format!("Invalid time '{}': {}. Expected RFC 3339, e.g. 2025-01-31T09:00:00Z", value, e))
+            })?;
+            cli_args.frozen_time = Some(time.with_timezone(&Utc));
+            continue;
+        }
+
+        if let Some(value) = take_option_value(&arg, "--data-dir", &mut args)? {
+            cli_args.data_dir = Some(PathBuf::from(value));
+            continue;
+        }
+
+        if let Some(value) = take_option_value(&arg, "--storage", &mut args)? {
+            cli_args.storage = Some(parse_storage(&value)?);
+            continue;
+        }
+
+        if let Some(value) = take_option_value(&arg, "--profile", &mut args)? {
+            profiles::validate_name(&value)?;
+            cli_args.profile = Some(value);
+            continue;
+        }
+
+        if let Some(value) = take_option_value(&arg, "--format", &mut args)? {
+            cli_args.format = parse_format(&value)?;
+            continue;
+        }
+
+        if arg == "--daily" {
+            cli_args.daily = true;
+            continue;
+        }
+
+        if let Some(value) = take_option_value(&arg, "--count", &mut args)? {
+            cli_args.card_count = value.parse().ok().filter(|&n| n > 0).ok_or_else(|| {
+                KanaError::InvalidInput(format!("Invalid count '{}': expected a positive integer", value))
+            })?;
+            continue;
+        }
+
+        if arg == "anki" && cli_args.command.is_none() {
+            let path = PathBuf::from(args.next().ok_or_else(|| {
+                KanaError::InvalidInput("Missing file for 'anki'; use '-' for stdout".to_string())
+            })?);
+            cli_args.command = Some(Command::Anki(path));
+            continue;
+        }
+
+        if (arg == "export" || arg == "import") && cli_args.command.is_none() {
+            let path = PathBuf::from(args.next().ok_or_else(|| {
+                KanaError::InvalidInput(format!("Missing file for '{}'; use '-' for stdin/stdout", arg))
+            })?);
+            cli_args.command = Some(if arg == "export" { Command::Export(path) } else { Command::Import(path) });
+            continue;
+        }
+
+        if arg == "merge" && cli_args.command.is_none() {
+            cli_args.command = Some(Command::Merge(Vec::new()));
+            continue;
+        }
+
+        // Everything after `merge` that isn't an option is a file to merge
+        if let Some(Command::Merge(files)) = cli_args.command.as_mut() {
+            if !arg.starts_with('-') {
+                files.push(PathBuf::from(arg));
+                continue;
+            }
+        }
+
+        if arg == "stats" && cli_args.command.is_none() {
+            cli_args.command = Some(Command::Stats);
+            continue;
+        }
+
+        if arg == "--yes" {
+            cli_args.confirmed = true;
+            continue;
+        }
+
+        if arg == "profile" && cli_args.command.is_none() {
+            let mut operand = |what: &str| {
+                args.next().ok_or_else(|| KanaError::InvalidInput(format!("Missing {} for 'profile'", what)))
+            };
+            let command = match operand("command")?.as_str() {
+                "list" => ProfileCommand::List,
+                "rename" => ProfileCommand::Rename { from: operand("profile name")?, to: operand("new name")? },
+                "delete" => ProfileCommand::Delete { name: operand("profile name")? },
+                "merge" => ProfileCommand::Merge { from: operand("profile name")?, into: operand("target profile")? },
+                other => {
+                    return Err(KanaError::InvalidInput(format!(
+                        "Unknown profile command '{}'. Valid commands are: list, rename, delete, merge",
+                        other
+                    )))
+                }
+            };
+            cli_args.command = Some(Command::Profile(command));
+            continue;
+        }
+
+        if arg == "convert" && cli_args.command.is_none() {
+            let target = args.next().ok_or_else(|| {
+                KanaError::InvalidInput(format!("Missing target for 'convert'. Valid options are: {:?}", VALID_STORAGE_FLAGS))
+            })?;
+            cli_args.command = Some(Command::Convert(parse_storage(&target)?));
+            continue;
+        }
+
+        if let Some(value) = take_option_value(&arg, "--autosave-every", &mut args)? {
+            cli_args.autosave_answers = value.parse().ok().filter(|&n| n > 0).ok_or_else(|| {
+                KanaError::InvalidInput(format!("Invalid answer count '{}': expected a positive integer", value))
+            })?;
+            continue;
+        }
+
+        if let Some(value) = take_option_value(&arg, "--autosave-interval", &mut args)? {
+            let secs: u64 = value.parse().ok().filter(|&n| n > 0).ok_or_else(|| {
+                KanaError::InvalidInput(format!("Invalid interval '{}': expected a positive number of seconds", value))
+            })?;
+            cli_args.autosave_interval = Duration::from_secs(secs);
+            continue;
+        }
+
+        if let Some(value) = take_option_value(&arg, "--reveal-after", &mut args)? {
+            cli_args.reveal_after = value.parse().map_err(|_| {
+                KanaError::InvalidInput(format!("Invalid failure count '{}': expected a whole number", value))
+            })?;
+            continue;
+        }
+
+        if let Some(value) = take_option_value(&arg, "--idle-after", &mut args)? {
+            let secs: u64 = value.parse().ok().filter(|&n| n > 0).ok_or_else(|| {
+                KanaError::InvalidInput(format!("Invalid idle threshold '{}': expected a positive number of seconds", value))
+            })?;
+            cli_args.idle_after = Duration::from_secs(secs);
+            continue;
+        }
+
+        if let Some(value) = take_option_value(&arg, "--goal", &mut args)? {
+            let count = value.parse().ok().filter(|&n| n > 0).ok_or_else(|| {
+                KanaError::InvalidInput(format!("Invalid goal '{}': expected a positive number of correct answers", value))
+            })?;
+            cli_args.goal = Some(DailyGoal::Correct(count));
+            continue;
+        }
+
+        if let Some(value) = take_option_value(&arg, "--goal-minutes", &mut args)? {
+            let minutes = value.parse().ok().filter(|&n| n > 0).ok_or_else(|| {
+                KanaError::InvalidInput(format!("Invalid goal '{}': expected a positive number of minutes", value))
+            })?;
+            cli_args.goal = Some(DailyGoal::Minutes(minutes));
+            continue;
+        }
+
+        if arg == "--instant" {
+            cli_args.instant = true;
+            continue;
+        }
+
+        if arg == "--due-only" {
+            cli_args.due_only = true;
+            continue;
+        }
+
         // Handle short flags (-h, -m, -mh etc)
         if arg.starts_with('-') && !arg.starts_with("--") {
             let flags = &arg[1..];
@@ -263,8 +676,8 @@ fn parse_args() -> Result<CliArgs> {
                 if let Some((kana_type, practice_type)) = parse_single_short_flag(flags) {
                     cli_args.kana_type = kana_type;
                     cli_args.practice_type = practice_type;
-                    kana_type_set = true;
-                    practice_type_set = true;
+                    cli_args.kana_type_set = true;
+                    cli_args.practice_type_set = true;
                     continue;
                 }
                 return Err(KanaError::InvalidInput(format!(
@@ -278,8 +691,8 @@ fn parse_args() -> Result<CliArgs> {
             if let Some((kana_type, practice_type)) = parse_combined_flags(flags) {
                 cli_args.kana_type = kana_type;
                 cli_args.practice_type = practice_type;
-                kana_type_set = true;
-                practice_type_set = true;
+                cli_args.kana_type_set = true;
+                cli_args.practice_type_set = true;
                 continue;
             }
 
@@ -293,23 +706,23 @@ fn parse_args() -> Result<CliArgs> {
         // Handle long flags (--hiragana, --main etc)
         if let Some(kana_type) = parse_kana_type(&arg) {
             cli_args.kana_type = kana_type;
-            kana_type_set = true;
+            cli_args.kana_type_set = true;
             continue;
         }
 
         if let Some(practice_type) = parse_practice_type(&arg) {
             cli_args.practice_type = practice_type;
-            practice_type_set = true;
+            cli_args.practice_type_set = true;
             continue;
         }
 
         // If we get here, the argument is unknown
-        if let Some(suggestion) = find_closest_match(&arg.trim_start_matches('-'), VALID_PRACTICE_FLAGS) {
+        if let Some(suggestion) = find_closest_match(arg.trim_start_matches('-'), VALID_PRACTICE_FLAGS) {
             return Err(KanaError::InvalidInput(format!(
                 "Unknown argument: '{}'. Did you mean '--{}'?",
                 arg, suggestion
             )));
-        } else if let Some(suggestion) = find_closest_match(&arg.trim_start_matches('-'), VALID_KANA_FLAGS) {
+        } else if let Some(suggestion) = find_closest_match(arg.trim_start_matches('-'), VALID_KANA_FLAGS) {
             return Err(KanaError::InvalidInput(format!(
                 "Unknown argument: '{}'. Did you mean '--{}'?",
                 arg, suggestion
@@ -324,9 +737,19 @@ fn parse_args() -> Result<CliArgs> {
 
     info!(
         kana_type = ?cli_args.kana_type,
-        kana_type_set = kana_type_set,
+        kana_type_set = cli_args.kana_type_set,
         practice_type = ?cli_args.practice_type,
-        practice_type_set = practice_type_set,
+        practice_type_set = cli_args.practice_type_set,
+        romanization = ?cli_args.romanization,
+        exercise = ?cli_args.exercise,
+        scheduler = ?cli_args.scheduler,
+        profile = ?cli_args.profile,
+        due_only = cli_args.due_only,
+        seed = ?cli_args.seed,
+        frozen_time = ?cli_args.frozen_time,
+        data_dir = ?cli_args.data_dir,
+        storage = ?cli_args.storage,
+        command = ?cli_args.command,
         "Parsed CLI arguments"
     );
 
@@ -347,6 +770,8 @@ fn run_app<B: ratatui::backend::Backend>(
     terminal: &mut Terminal<B>,
     app: &mut App,
     tick_rate: Duration,
+    session: &mut ProfileSession,
+    terminate: &AtomicBool,
 ) -> Result<()> {
     let mut last_tick = Instant::now();
 
@@ -360,21 +785,53 @@ fn run_app<B: ratatui::backend::Backend>(
         if event::poll(timeout)? {
             if let Event::Key(key) = event::read()? {
                 match key.code {
+                    // Raw mode swallows SIGINT, so treat Ctrl+C like Esc
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        app.should_quit = true;
+                    }
+                    KeyCode::Tab => {
+                        app.take_hint();
+                    }
+                    KeyCode::Char('?') if app.state.mode == AppMode::Feedback => {
+                        app.reveal_answer();
+                    }
+                    KeyCode::Char('?') if app.state.mode == AppMode::Ready => {
+                        app.toggle_mnemonic();
+                    }
+                    KeyCode::Char(c) if app.state.mode == AppMode::Ready => {
+                        app.handle_input(c)?;
+                    }
                     KeyCode::Char(c) => {
-                        if app.state.mode == AppMode::Ready {
-                            app.handle_input(c)
-                        }
+                        app.handle_settings_key(c);
                     }
                     KeyCode::Enter => {
                         app.handle_enter()?;
                     }
-                    KeyCode::Backspace => {
-                        if app.state.mode == AppMode::Ready {
-                            app.state.input_buffer.pop();
-                        }
+                    KeyCode::Backspace if app.state.mode == AppMode::Ready => {
+                        app.handle_backspace();
                     }
                     KeyCode::Esc => {
-                        app.should_quit = true;
+                        // The first Esc shows how the session went, unless nothing was answered
+                        app.should_quit = app.state.mode == AppMode::Summary || !app.show_summary();
+                    }
+                    // Profile picker
+                    KeyCode::Up | KeyCode::Down
+                        if app.state.mode == AppMode::Initial && app.state.profiles.len() > 1 =>
+                    {
+                        let count = app.state.profiles.len();
+                        let current = app.state.profiles
+                            .iter()
+                            .position(|profile| *profile == app.state.profile)
+                            .unwrap_or(0);
+                        let next = if key.code == KeyCode::Down {
+                            (current + 1) % count
+                        } else {
+                            (current + count - 1) % count
+                        };
+                        let name = app.state.profiles[next].clone();
+                        if let Err(e) = session.switch(app, &name) {
+                            warn!("Failed to switch to profile '{}': {}", name, e);
+                        }
                     }
                     _ => {}
                 }
@@ -385,6 +842,15 @@ fn run_app<B: ratatui::backend::Backend>(
             last_tick = Instant::now();
         }
 
+        if let Err(e) = session.tick(app) {
+            warn!("Autosave failed: {}", e);
+        }
+
+        if terminate.load(Ordering::SeqCst) {
+            info!("Termination signal received, shutting down");
+            app.should_quit = true;
+        }
+
         if app.should_quit {
             break;
         }
@@ -393,42 +859,235 @@ fn run_app<B: ratatui::backend::Backend>(
     Ok(())
 }
 
-fn load_history(app: &mut App) -> Result<()> {
-    if Path::new(HISTORY_FILE).exists() {
-        let file = File::open(HISTORY_FILE)?;
-        app.state.history = serde_json::from_reader(file)?;
-
-        for (kana, stats) in app.state.history.character_stats.iter_mut() {
-            let stored_ema_response = stats.exp_avg_response;
-            let stored_ema_accuracy = stats.exp_avg_accuracy;
-
-            stats.recalculate_ema();
-
-            if (stats.exp_avg_response - stored_ema_response).abs() > 1e-10
-                || (stats.exp_avg_accuracy - stored_ema_accuracy).abs() > 1e-10
-            {
-                warn!(
-                    kana = kana,
-                    stored_response = stored_ema_response,
-                    stored_accuracy = stored_ema_accuracy,
-                    recalculated_response = stats.exp_avg_response,
-                    recalculated_accuracy = stats.exp_avg_accuracy,
-                    "EMA mismatch detected"
+/// Sets the returned flag on SIGTERM or SIGHUP (e.g. a closed terminal window),
+/// so the main loop can save history and exit cleanly.
+#[cfg(unix)]
+fn watch_termination_signals() -> Arc<AtomicBool> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let flag = Arc::new(AtomicBool::new(false));
+    let notify = Arc::clone(&flag);
+
+    thread::spawn(move || {
+        let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+            Ok(runtime) => runtime,
+            Err(e) => {
+                warn!("Failed to start signal handler: {}", e);
+                return;
+            }
+        };
+        runtime.block_on(async move {
+            let (Ok(mut terminate), Ok(mut hangup)) =
+                (signal(SignalKind::terminate()), signal(SignalKind::hangup()))
+            else {
+                warn!("Failed to install signal handlers");
+                return;
+            };
+            tokio::select! {
+                _ = terminate.recv() => info!("Received SIGTERM"),
+                _ = hangup.recv() => info!("Received SIGHUP"),
+            }
+            notify.store(true, Ordering::SeqCst);
+        });
+    });
+
+    flag
+}
+
+#[cfg(not(unix))]
+fn watch_termination_signals() -> Arc<AtomicBool> {
+    Arc::new(AtomicBool::new(false))
+}
+
+/// Restores the terminal before the default panic message is printed,
+/// so it isn't lost in the alternate screen.
+fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let _ = disable_raw_mode();
+        let _ = execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture);
+        error!("Panic: {}", info);
+        default_hook(info);
+    }));
+}
+
+fn restore_terminal<B: ratatui::backend::Backend + io::Write>(terminal: &mut Terminal<B>) -> Result<()> {
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
+    terminal.show_cursor()?;
+    Ok(())
+}
+
+fn run_profile_command(command: ProfileCommand, data_dir: &std::path::Path, confirmed: bool) -> Result<()> {
+    match command {
+        ProfileCommand::List => {
+            for name in profiles::list(data_dir)? {
+                let dir = paths::profile_dir(data_dir, &name);
+                println!(
+                    "{:<20} {:<7} {} attempts",
+                    name,
+                    storage::detect(&dir).to_string(),
+                    profiles::attempt_count(data_dir, &name)?
                 );
             }
         }
+        ProfileCommand::Rename { from, to } => {
+            profiles::rename(data_dir, &from, &to)?;
+            println!("Renamed profile '{}' to '{}'", from, to);
+        }
+        ProfileCommand::Delete { name } => {
+            let attempts = profiles::attempt_count(data_dir, &name)?;
+            if !confirmed {
+                return Err(KanaError::InvalidInput(format!(
+                    "Deleting profile '{}' removes {} attempts; run again with --yes to confirm",
+                    name, attempts
+                )));
+            }
+            profiles::delete(data_dir, &name)?;
+            println!("Deleted profile '{}' ({} attempts)", name, attempts);
+        }
+        ProfileCommand::Merge { from, into } => {
+            let attempts = profiles::attempt_count(data_dir, &from)?;
+            if !confirmed {
+                return Err(KanaError::InvalidInput(format!(
+                    "Merging removes profile '{}' after adding its {} attempts to '{}'; run again with --yes to confirm",
+                    from, attempts, into
+                )));
+            }
+            profiles::merge(data_dir, &from, &into)?;
+            println!(
+                "Merged profile '{}' into '{}', which now has {} attempts",
+                from,
+                into,
+                profiles::attempt_count(data_dir, &into)?
+            );
+        }
     }
     Ok(())
 }
 
-fn save_history(app: &App) -> Result<()> {
-    let file = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open(HISTORY_FILE)?;
+// The kana type, practice set and exercise flags narrow down reports and exports
+fn report_filter(cli_args: &CliArgs) -> ReportFilter {
+    let (practice_mode, kana_type) = convert_to_practice_mode(cli_args);
+    ReportFilter {
+        kana_type: cli_args.kana_type_set.then_some(kana_type),
+        practice_mode: cli_args.practice_type_set.then_some(practice_mode),
+        exercise: cli_args.exercise.unwrap_or_default(),
+    }
+}
 
-    serde_json::to_writer_pretty(file, &app.state.history)?;
+fn run_command(command: Command, data_dir: &std::path::Path, cli_args: &CliArgs) -> Result<()> {
+    match command {
+        Command::Convert(to) => {
+            let profile = cli_args.profile.as_deref().unwrap_or(DEFAULT_PROFILE);
+            let profile_dir = paths::profile_dir(data_dir, profile);
+            let history = storage::convert(&profile_dir, to)?;
+            println!("Converted {} attempts of profile '{}' to {}", history.attempt_count(), profile, to);
+        }
+        Command::Profile(command) => run_profile_command(command, data_dir, cli_args.confirmed)?,
+        Command::Stats => {
+            let profile = cli_args.profile.as_deref().unwrap_or(DEFAULT_PROFILE);
+            let history = profiles::history(data_dir, profile)?;
+            let filter = report_filter(cli_args);
+            let settings = profiles::ProfileSettings::load(&paths::profile_dir(data_dir, profile))?;
+            let goal = cli_args.goal.unwrap_or(settings.goal);
+            let romanization = cli_args.romanization.unwrap_or(settings.romanization);
+            let report = Report::build(profile, &history, filter, romanization).with_goal(&history, goal);
+            print!("{}", report.render(cli_args.format, cli_args.daily)?);
+        }
+        Command::Anki(path) => {
+            let profile = cli_args.profile.as_deref().unwrap_or(DEFAULT_PROFILE);
+            let history = profiles::history(data_dir, profile)?;
+            let romanization = match cli_args.romanization {
+                Some(romanization) => romanization,
+                None => profiles::ProfileSettings::load(&paths::profile_dir(data_dir, profile))?.romanization,
+            };
+            let filter = report_filter(cli_args);
+            let notes = if path.as_os_str() == "-" {
+                anki::export_deck(&history, filter, romanization, cli_args.card_count, io::stdout().lock())?
+            } else {
+                let mut writer = io::BufWriter::new(std::fs::File::create(&path)?);
+                let notes = anki::export_deck(&history, filter, romanization, cli_args.card_count, &mut writer)?;
+                io::Write::flush(&mut writer)?;
+                notes
+            };
+            info!(profile = profile, notes = notes, path = %path.display(), "Exported Anki deck");
+            if path.as_os_str() != "-" {
+                println!("Wrote {} notes for the weakest kana of profile '{}' to {}", notes, profile, path.display());
+            }
+        }
+        Command::Export(path) => {
+            let profile = cli_args.profile.as_deref().unwrap_or(DEFAULT_PROFILE);
+            let history = profiles::history(data_dir, profile)?;
+            let rows = if path.as_os_str() == "-" {
+                transfer::export_csv(&history, io::stdout().lock())?
+            } else {
+                let mut writer = io::BufWriter::new(std::fs::File::create(&path)?);
+                let rows = transfer::export_csv(&history, &mut writer)?;
+                io::Write::flush(&mut writer)?;
+                rows
+            };
+            info!(profile = profile, rows = rows, path = %path.display(), "Exported attempts");
+            if path.as_os_str() != "-" {
+                println!("Exported {} attempts of profile '{}' to {}", rows, profile, path.display());
+            }
+        }
+        Command::Merge(files) => {
+            if files.is_empty() {
+                return Err(KanaError::InvalidInput(
+                    "Missing history files for 'merge'".to_string(),
+                ));
+            }
+            let profile = cli_args.profile.as_deref().unwrap_or(DEFAULT_PROFILE);
+            profiles::validate_name(profile)?;
+            let profile_dir = paths::profile_dir(data_dir, profile);
+            let mut store = storage::open(
+                cli_args.storage.unwrap_or_else(|| storage::detect(&profile_dir)),
+                &profile_dir,
+            );
+            let mut history = store.load()?;
+
+            for file in &files {
+                if !file.is_file() {
+                    return Err(KanaError::InvalidInput(format!("{} does not exist", file.display())));
+                }
+                let other = storage::open_file(file).load()?;
+                let before = history.attempt_count();
+                history.merge(&other);
+                let added = history.attempt_count() - before;
+                info!(file = %file.display(), added = added, "Merged history file");
+                println!("{}: {} attempts, {} new", file.display(), other.attempt_count(), added);
+            }
+
+            store.save(&history)?;
+            println!("Profile '{}' now has {} attempts", profile, history.attempt_count());
+        }
+        Command::Import(path) => {
+            let profile = cli_args.profile.as_deref().unwrap_or(DEFAULT_PROFILE);
+            profiles::validate_name(profile)?;
+            let profile_dir = paths::profile_dir(data_dir, profile);
+            let mut store = storage::open(
+                cli_args.storage.unwrap_or_else(|| storage::detect(&profile_dir)),
+                &profile_dir,
+            );
+            let mut history = store.load()?;
+            let (rows, added) = if path.as_os_str() == "-" {
+                transfer::import_csv(&mut history, io::stdin().lock())?
+            } else {
+                transfer::import_csv(&mut history, io::BufReader::new(std::fs::File::open(&path)?))?
+            };
+            store.save(&history)?;
+            info!(profile = profile, rows = rows, added = added, path = %path.display(), "Imported attempts");
+            println!(
+                "Read {} attempts, added {} new ones to profile '{}'",
+                rows, added, profile
+            );
+        }
+    }
     Ok(())
 }
 
@@ -437,45 +1096,114 @@ fn main() -> Result<()> {
     info!("Starting kana practice application");
 
     let cli_args = parse_args()?;
+    let data_dir = paths::data_dir(cli_args.data_dir.as_deref());
+    // Reproducible sessions leave every file where it is, older layouts included
+    let read_only = cli_args.frozen_time.is_some();
+    if read_only {
+        info!("Reproducible session, not moving history from older locations");
+    } else {
+        if let Err(e) = paths::migrate_to_profiles(&data_dir, DEFAULT_PROFILE) {
+            warn!("Failed to move history into the default profile: {}", e);
+        }
+        if let Err(e) = paths::migrate_legacy_history(&paths::profile_dir(&data_dir, DEFAULT_PROFILE)) {
+            warn!("Failed to migrate history from the working directory: {}", e);
+        }
+    }
 
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+    if let Some(command) = cli_args.command.clone() {
+        return run_command(command, &data_dir, &cli_args);
+    }
 
     let mut app = App::new();
-    let (practice_mode, kana_type) = convert_to_practice_mode(&cli_args);
-    app.set_practice_mode(practice_mode);
-    app.set_kana_type(kana_type);
+    let mut session = ProfileSession::new(
+        data_dir,
+        cli_args.storage,
+        cli_args.autosave_answers,
+        cli_args.autosave_interval,
+        read_only,
+    );
+    // Starting empty would overwrite an unreadable file on the next save
+    let profile = cli_args.profile.as_deref().unwrap_or(DEFAULT_PROFILE);
+    session
+        .open(&mut app, profile)
+        .inspect_err(|e| error!("Failed to load profile '{}': {}", profile, e))?;
+    info!(profile = profile, "Successfully loaded history");
 
-    match load_history(&mut app) {
-        Ok(_) => info!("Successfully loaded history"),
-        Err(e) => warn!("Failed to load history: {}", e),
+    let (practice_mode, kana_type) = convert_to_practice_mode(&cli_args);
+    if cli_args.practice_type_set {
+        app.set_practice_mode(practice_mode);
+    }
+    if cli_args.kana_type_set {
+        app.set_kana_type(kana_type);
+    }
+    if let Some(romanization) = cli_args.romanization {
+        app.set_romanization(romanization);
+    }
+    if let Some(exercise) = cli_args.exercise {
+        app.set_exercise(exercise);
+    }
+    if cli_args.scheduler.is_some() || cli_args.due_only {
+        let scheduler = cli_args.scheduler.unwrap_or(app.state.scheduler);
+        app.set_scheduler(scheduler, cli_args.due_only || app.state.due_only);
+    }
+    if cli_args.instant {
+        app.set_instant(true);
+    }
+    if let Some(goal) = cli_args.goal {
+        app.set_goal(goal);
     }
+    app.set_reveal_after(cli_args.reveal_after);
+    app.set_idle_after(cli_args.idle_after);
 
-    app.select_next_kana()?;
+    // Always run seeded and log the seed, so any session can be replayed from the logs
+    let seed = cli_args.seed.unwrap_or_else(rand::random);
+    app.set_seed(seed);
+    info!(seed = seed, "Session RNG seeded");
 
-    let tick_rate = Duration::from_millis(250);
-    let res = run_app(&mut terminal, &mut app, tick_rate);
+    if let Some(time) = cli_args.frozen_time {
+        app.set_clock(Box::new(FixedClock::new(time)));
+        info!(frozen_time = %time, "Reproducible session: clock frozen, history will not be saved");
+    }
 
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+    let terminate = watch_termination_signals();
+    install_panic_hook();
+
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
 
-    if let Err(e) = save_history(&app) {
+    let tick_rate = Duration::from_millis(250);
+    let res = panic::catch_unwind(AssertUnwindSafe(|| {
+        app.select_next_kana()?;
+        run_app(&mut terminal, &mut app, tick_rate, &mut session, &terminate)
+    }));
+
+    // Ctrl+C and signals skip the summary, but the session still counts
+    app.end_session();
+    // Save before touching the terminal, which may already be gone after SIGHUP
+    if cli_args.frozen_time.is_some() {
+        info!("Reproducible session, history left unchanged");
+    } else if let Err(e) = session.save(&mut app) {
         error!("Failed to save history: {}", e);
-    } else {
-        info!("Successfully saved history");
     }
 
-    if let Err(err) = res {
-        error!("Application error: {}", err);
-        println!("Error: {}", err);
+    if let Err(e) = restore_terminal(&mut terminal) {
+        error!("Failed to restore terminal: {}", e);
+    }
+
+    match res {
+        Ok(Ok(())) => {}
+        Ok(Err(err)) => {
+            error!("Application error: {}", err);
+            println!("Error: {}", err);
+        }
+        Err(_) => {
+            return Err(KanaError::Terminal(
+                "The application panicked, see the log for details".to_string(),
+            ));
+        }
     }
 
     info!("Application terminated");
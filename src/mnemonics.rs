@@ -0,0 +1,41 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use crate::error::Result;
+use crate::kana::lookup_entry;
+use crate::paths;
+
+/// Mnemonics for the kana of one profile: the built-in ones from `kana.rs`,
+/// overridden by the profile's own `mnemonics.json`.
+///
+/// The file maps kana to text, e.g. `{"ぬ": "ぬ has a loop — noodle"}`.
+/// An empty text hides the built-in mnemonic for that kana.
+#[derive(Debug, Clone, Default)]
+pub struct Mnemonics {
+    overrides: HashMap<String, String>,
+}
+
+impl Mnemonics {
+    /// Reads the overrides in `profile_dir`, if there are any.
+    pub fn load(profile_dir: &Path) -> Result<Self> {
+        let path = paths::mnemonics_path(profile_dir);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let overrides = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        Ok(Self { overrides })
+    }
+
+    pub fn override_count(&self) -> usize {
+        self.overrides.len()
+    }
+
+    pub fn get(&self, kana: &str) -> Option<&str> {
+        match self.overrides.get(kana) {
+            Some(text) => Some(text.as_str()).filter(|text| !text.trim().is_empty()),
+            None => lookup_entry(kana).and_then(|entry| entry.mnemonic),
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::Result;
+use tracing::{info, warn};
+
+const APP_DIR: &str = "kana_practice";
+const HISTORY_FILE: &str = "kana_history.json";
+const DATABASE_FILE: &str = "kana_history.db";
+const MNEMONICS_FILE: &str = "mnemonics.json";
+const PROFILES_DIR: &str = "profiles";
+const DATA_DIR_ENV: &str = "KANA_PRACTICE_DATA_DIR";
+const LOG_DIR_ENV: &str = "KANA_PRACTICE_LOG_DIR";
+
+// Reads an XDG base directory variable. The spec says relative paths must be ignored.
+fn xdg_dir(var: &str) -> Option<PathBuf> {
+    env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+}
+
+fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+}
+
+/// Where history is stored: `--data-dir`, then `$KANA_PRACTICE_DATA_DIR`,
+/// then `$XDG_DATA_HOME/kana_practice`, then `~/.local/share/kana_practice`.
+pub fn data_dir(cli_override: Option<&Path>) -> PathBuf {
+    if let Some(dir) = cli_override {
+        return dir.to_path_buf();
+    }
+    if let Some(dir) = env::var_os(DATA_DIR_ENV).filter(|dir| !dir.is_empty()) {
+        return PathBuf::from(dir);
+    }
+    if let Some(dir) = xdg_dir("XDG_DATA_HOME") {
+        return dir.join(APP_DIR);
+    }
+    match home_dir() {
+        Some(home) => home.join(".local").join("share").join(APP_DIR),
+        None => {
+            warn!("Neither XDG_DATA_HOME nor HOME is set, storing history in the working directory");
+            PathBuf::from(".")
+        }
+    }
+}
+
+/// Where logs are written: `$KANA_PRACTICE_LOG_DIR`, then
+/// `$XDG_STATE_HOME/kana_practice/logs`, then `~/.local/state/kana_practice/logs`.
+pub fn log_dir() -> PathBuf {
+    if let Some(dir) = env::var_os(LOG_DIR_ENV).filter(|dir| !dir.is_empty()) {
+        return PathBuf::from(dir);
+    }
+    let state_home = xdg_dir("XDG_STATE_HOME")
+        .or_else(|| home_dir().map(|home| home.join(".local").join("state")));
+    match state_home {
+        Some(dir) => dir.join(APP_DIR).join("logs"),
+        None => PathBuf::from("logs"),
+    }
+}
+
+pub fn history_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(HISTORY_FILE)
+}
+
+pub fn database_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(DATABASE_FILE)
+}
+
+pub fn mnemonics_path(profile_dir: &Path) -> PathBuf {
+    profile_dir.join(MNEMONICS_FILE)
+}
+
+pub fn profiles_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(PROFILES_DIR)
+}
+
+pub fn profile_dir(data_dir: &Path, profile: &str) -> PathBuf {
+    profiles_dir(data_dir).join(profile)
+}
+
+/// Moves history kept directly in the data directory, as versions without
+/// profiles did, into `profile`. Backups and converted files move along.
+/// Does nothing once the profiles directory exists.
+pub fn migrate_to_profiles(data_dir: &Path, profile: &str) -> Result<()> {
+    if profiles_dir(data_dir).exists() || !data_dir.is_dir() {
+        return Ok(());
+    }
+
+    let mut files = Vec::new();
+    for entry in fs::read_dir(data_dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() && entry.file_name().to_string_lossy().starts_with("kana_history") {
+            files.push(entry.file_name());
+        }
+    }
+    if files.is_empty() {
+        return Ok(());
+    }
+
+    let target = profile_dir(data_dir, profile);
+    fs::create_dir_all(&target)?;
+    for name in &files {
+        fs::rename(data_dir.join(name), target.join(name))?;
+    }
+
+    info!(to = %target.display(), files = files.len(), "Moved history into a profile");
+    Ok(())
+}
+
+/// Moves a `kana_history.json` left in the working directory by older versions
+/// into `profile_dir`. Does nothing if the profile already has one.
+/// The old file is kept as `kana_history.json.migrated`.
+pub fn migrate_legacy_history(profile_dir: &Path) -> Result<()> {
+    let legacy = PathBuf::from(HISTORY_FILE);
+    let target = history_path(profile_dir);
+
+    if !legacy.is_file() || target.exists() || database_path(profile_dir).exists() {
+        return Ok(());
+    }
+
+    fs::create_dir_all(profile_dir)?;
+    fs::copy(&legacy, &target)?;
+    fs::rename(&legacy, legacy.with_extension("json.migrated"))?;
+
+    info!(
+        from = %legacy.display(),
+        to = %target.display(),
+        "Migrated history from the working directory"
+    );
+    Ok(())
+}
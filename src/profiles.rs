@@ -0,0 +1,284 @@
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::app::App;
+use crate::error::{KanaError, Result};
+use crate::mnemonics::Mnemonics;
+use crate::paths;
+use crate::storage::{self, Autosaver};
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+pub const DEFAULT_PROFILE: &str = "default";
+const SETTINGS_FILE: &str = "settings.json";
+const MAX_NAME_LEN: usize = 32;
+
+/// The practice options a profile starts with. Saved on exit, so each
+/// learner picks up where they left off; command-line flags override them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileSettings {
+    pub kana_type: KanaType,
+    pub practice_mode: PracticeMode,
+    pub romanization: Romanization,
+    pub exercise: ExerciseMode,
+    pub scheduler: SchedulerKind,
+    pub due_only: bool,
+    pub instant: bool,
+    pub goal: DailyGoal,
+}
+
+impl Default for ProfileSettings {
+    fn default() -> Self {
+        Self {
+            kana_type: KanaType::Hiragana,
+            practice_mode: PracticeMode::Main,
+            romanization: Romanization::default(),
+            exercise: ExerciseMode::default(),
+            scheduler: SchedulerKind::default(),
+            due_only: false,
+            instant: false,
+            goal: DailyGoal::default(),
+        }
+    }
+}
+
+impl ProfileSettings {
+    pub fn from_state(state: &AppState) -> Self {
+        Self {
+            kana_type: state.kana_type,
+            practice_mode: state.practice_mode,
+            romanization: state.romanization,
+            exercise: state.exercise,
+            scheduler: state.scheduler,
+            due_only: state.due_only,
+            instant: state.instant,
+            goal: state.goal,
+        }
+    }
+
+    pub fn apply(&self, app: &mut App) {
+        app.set_kana_type(self.kana_type);
+        app.set_practice_mode(self.practice_mode);
+        app.set_romanization(self.romanization);
+        app.set_exercise(self.exercise);
+        app.set_scheduler(self.scheduler, self.due_only);
+        app.set_instant(self.instant);
+        app.set_goal(self.goal);
+    }
+
+    /// Reads the settings in `profile_dir`, or the defaults if there are none yet.
+    pub fn load(profile_dir: &Path) -> Result<Self> {
+        let path = profile_dir.join(SETTINGS_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_reader(BufReader::new(File::open(path)?))?)
+    }
+
+    pub fn save(&self, profile_dir: &Path) -> Result<()> {
+        fs::create_dir_all(profile_dir)?;
+        let path = profile_dir.join(SETTINGS_FILE);
+        let tmp_path = profile_dir.join(format!("{}.tmp", SETTINGS_FILE));
+        {
+            let mut writer = BufWriter::new(File::create(&tmp_path)?);
+            serde_json::to_writer_pretty(&mut writer, self)?;
+            writer.flush()?;
+        }
+        fs::rename(tmp_path, path)?;
+        Ok(())
+    }
+}
+
+/// Profile names become directory names, so only letters, digits, `-` and `_` are allowed.
+pub fn validate_name(name: &str) -> Result<()> {
+    let valid_chars = name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    if name.is_empty() || name.chars().count() > MAX_NAME_LEN || !valid_chars {
+        return Err(KanaError::InvalidInput(format!(
+            "Invalid profile name '{}': use up to {} letters, digits, '-' or '_'",
+            name, MAX_NAME_LEN
+        )));
+    }
+    Ok(())
+}
+
+/// All profiles in the data directory, sorted by name.
+pub fn list(data_dir: &Path) -> Result<Vec<String>> {
+    let dir = paths::profiles_dir(data_dir);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut names = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if entry.file_type()?.is_dir() && validate_name(&name).is_ok() {
+            names.push(name);
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+fn existing_dir(data_dir: &Path, name: &str) -> Result<PathBuf> {
+    validate_name(name)?;
+    let dir = paths::profile_dir(data_dir, name);
+    if !dir.is_dir() {
+        return Err(KanaError::InvalidInput(format!("Profile '{}' does not exist", name)));
+    }
+    Ok(dir)
+}
+
+fn load_history(profile_dir: &Path) -> Result<UserHistory> {
+    storage::open(storage::detect(profile_dir), profile_dir).load()
+}
+
+/// Loads the history of an existing profile without opening it for practice.
+pub fn history(data_dir: &Path, name: &str) -> Result<UserHistory> {
+    load_history(&existing_dir(data_dir, name)?)
+}
+
+/// Number of recorded attempts in a profile, for listings and confirmations.
+pub fn attempt_count(data_dir: &Path, name: &str) -> Result<usize> {
+    Ok(history(data_dir, name)?.attempt_count())
+}
+
+pub fn rename(data_dir: &Path, from: &str, to: &str) -> Result<()> {
+    let source = existing_dir(data_dir, from)?;
+    validate_name(to)?;
+    let target = paths::profile_dir(data_dir, to);
+    if target.exists() {
+        return Err(KanaError::InvalidInput(format!("Profile '{}' already exists", to)));
+    }
+    fs::rename(source, target)?;
+    info!(from = from, to = to, "Renamed profile");
+    Ok(())
+}
+
+pub fn delete(data_dir: &Path, name: &str) -> Result<()> {
+    fs::remove_dir_all(existing_dir(data_dir, name)?)?;
+    info!(profile = name, "Deleted profile");
+    Ok(())
+}
+
+/// Folds the history of `from` into `into` and removes `from`.
+/// The settings of `into` are kept.
+pub fn merge(data_dir: &Path, from: &str, into: &str) -> Result<UserHistory> {
+    if from == into {
+        return Err(KanaError::InvalidInput("Cannot merge a profile into itself".to_string()));
+    }
+    let source_dir = existing_dir(data_dir, from)?;
+    let target_dir = existing_dir(data_dir, into)?;
+
+    let source = load_history(&source_dir)?;
+    let mut target_store = storage::open(storage::detect(&target_dir), &target_dir);
+    let mut history = target_store.load()?;
+    history.merge(&source);
+    target_store.save(&history)?;
+
+    fs::remove_dir_all(source_dir)?;
+    info!(from = from, into = into, "Merged profiles");
+    Ok(history)
+}
+
+/// The profile being practised in the TUI: owns its storage and autosave,
+/// and swaps everything over when the learner picks another profile.
+pub struct ProfileSession {
+    data_dir: PathBuf,
+    storage: Option<StorageKind>,
+    autosave_answers: u32,
+    autosave_interval: Duration,
+    // Reproducible sessions never write anything
+    read_only: bool,
+    dir: PathBuf,
+    autosaver: Option<Autosaver>,
+}
+
+impl ProfileSession {
+    pub fn new(
+        data_dir: PathBuf,
+        storage: Option<StorageKind>,
+        autosave_answers: u32,
+        autosave_interval: Duration,
+        read_only: bool,
+    ) -> Self {
+        Self {
+            dir: data_dir.clone(),
+            data_dir,
+            storage,
+            autosave_answers,
+            autosave_interval,
+            read_only,
+            autosaver: None,
+        }
+    }
+
+    /// Loads the history and settings of `name` into the app, creating the
+    /// profile if needed. The app is left untouched if anything fails.
+    pub fn open(&mut self, app: &mut App, name: &str) -> Result<()> {
+        validate_name(name)?;
+        let dir = paths::profile_dir(&self.data_dir, name);
+        let kind = self.storage.unwrap_or_else(|| storage::detect(&dir));
+        let mut store = storage::open(kind, &dir);
+        info!(profile = name, path = %store.path().display(), storage = %kind, "Opening profile");
+
+        let history = store.load()?;
+        let settings = ProfileSettings::load(&dir)?;
+        // A typo in a hand-edited file shouldn't keep anyone from practising
+        let mnemonics = Mnemonics::load(&dir).unwrap_or_else(|e| {
+            warn!(profile = name, "Failed to read mnemonics, using the built-in ones: {}", e);
+            Mnemonics::default()
+        });
+        info!(profile = name, overrides = mnemonics.override_count(), "Loaded mnemonics");
+
+        if !self.read_only {
+            fs::create_dir_all(&dir)?;
+        }
+        settings.apply(app);
+        app.state.history = history;
+        app.state.mnemonics = mnemonics;
+        app.state.unsaved_answers = 0;
+        app.state.profile = name.to_string();
+        app.state.profiles = list(&self.data_dir)?;
+        if !app.state.profiles.iter().any(|profile| profile == name) {
+            app.state.profiles.push(name.to_string());
+            app.state.profiles.sort();
+        }
+
+        self.dir = dir;
+        self.autosaver = (!self.read_only)
+            .then(|| Autosaver::new(store, self.autosave_answers, self.autosave_interval));
+        Ok(())
+    }
+
+    /// Saves the current profile, then opens `name`.
+    pub fn switch(&mut self, app: &mut App, name: &str) -> Result<()> {
+        app.end_session();
+        self.save(app)?;
+        self.open(app, name)
+    }
+
+    /// Saves history and settings of the current profile.
+    pub fn save(&mut self, app: &mut App) -> Result<()> {
+        if let Some(autosaver) = self.autosaver.as_mut() {
+            autosaver.save(&app.state.history)?;
+            ProfileSettings::from_state(&app.state).save(&self.dir)?;
+            app.state.unsaved_answers = 0;
+        }
+        Ok(())
+    }
+
+    /// Autosaves the history when enough answers or time have accumulated.
+    pub fn tick(&mut self, app: &mut App) -> Result<()> {
+        if let Some(autosaver) = self.autosaver.as_mut() {
+            if autosaver.tick(&app.state.history, app.state.unsaved_answers)? {
+                app.state.unsaved_answers = 0;
+            }
+        }
+        Ok(())
+    }
+}
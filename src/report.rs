@@ -0,0 +1,309 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::error::Result;
+use crate::goals::{daily_totals, streak, Calendar, Streak, LEGEND};
+use crate::kana::kana_set;
+use crate::types::*;
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use serde::Serialize;
+
+const WEAKEST_COUNT: usize = 5;
+// About half a year, which still fits in 60 columns
+const CALENDAR_WEEKS: u16 = 26;
+
+/// How the `stats` command prints its report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportFormat {
+    #[default]
+    Table,
+    Json,
+    Csv,
+}
+
+/// Which part of the history a report covers. `None` means no filter.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReportFilter {
+    pub kana_type: Option<KanaType>,
+    pub practice_mode: Option<PracticeMode>,
+    pub exercise: ExerciseMode,
+}
+
+#[derive(Debug, Serialize)]
+pub struct KanaRow {
+    pub kana: String,
+    pub romaji: String,
+    pub kana_type: KanaType,
+    pub set: PracticeMode,
+    pub attempts: u32,
+    pub successes: u32,
+    pub failures: u32,
+    pub accuracy: f64,
+    pub ema_accuracy: f64,
+    pub ema_response_ms: f64,
+    pub last_seen: DateTime<Utc>,
+    pub due: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DailyRow {
+    pub date: NaiveDate,
+    pub attempts: u32,
+    pub successes: u32,
+    pub accuracy: f64,
+    pub practice_seconds: f64,
+}
+
+/// A non-interactive summary of one profile's progress.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub profile: String,
+    pub exercise: ExerciseMode,
+    pub kana: Vec<KanaRow>,
+    /// Lowest recent accuracy first, slowest first on ties.
+    pub weakest: Vec<String>,
+    pub daily: Vec<DailyRow>,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub goal: Option<GoalSummary>,
+}
+
+/// Progress towards the daily goal. Covers every session, whatever the filter.
+#[derive(Debug, Serialize)]
+pub struct GoalSummary {
+    pub goal: DailyGoal,
+    /// Share of today's goal reached so far; 1.0 or more means it was met.
+    pub goal_progress: f64,
+    pub streak: Streak,
+    #[serde(skip)]
+    status: String,
+    #[serde(skip)]
+    calendar: Vec<String>,
+}
+
+fn ratio(part: u32, whole: u32) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+impl Report {
+    /// Kana are listed with their reading under `romanization`.
+    pub fn build(profile: &str, history: &UserHistory, filter: ReportFilter, romanization: Romanization) -> Self {
+        let stats = history.stats_for(filter.exercise);
+        let kana_types = match filter.kana_type {
+            Some(kana_type) => vec![kana_type],
+            None => vec![KanaType::Hiragana, KanaType::Katakana],
+        };
+        let sets = match filter.practice_mode {
+            Some(PracticeMode::All) | None => {
+                vec![PracticeMode::Main, PracticeMode::Dakuten, PracticeMode::Combination]
+            }
+            Some(mode) => vec![mode],
+        };
+
+        let mut kana = Vec::new();
+        let mut daily: BTreeMap<NaiveDate, DailyRow> = BTreeMap::new();
+        for &kana_type in &kana_types {
+            for &set in &sets {
+                for entry in kana_set(kana_type, set) {
+                    let Some(stats) = stats.get(entry.kana).filter(|stats| stats.appearances > 0) else {
+                        continue;
+                    };
+                    kana.push(KanaRow {
+                        kana: entry.kana.to_string(),
+                        romaji: entry.romaji(romanization).to_string(),
+                        kana_type,
+                        set,
+                        attempts: stats.appearances,
+                        successes: stats.successes,
+                        failures: stats.failures,
+                        accuracy: ratio(stats.successes, stats.appearances),
+                        ema_accuracy: stats.exp_avg_accuracy,
+                        ema_response_ms: stats.exp_avg_response,
+                        last_seen: stats.last_appearance,
+                        due: stats.due,
+                    });
+
+                    for attempt in &stats.test_history {
+                        let date = attempt.start_time.with_timezone(&Local).date_naive();
+                        let day = daily.entry(date).or_insert_with(|| DailyRow {
+                            date,
+                            attempts: 0,
+                            successes: 0,
+                            accuracy: 0.0,
+                            practice_seconds: 0.0,
+                        });
+                        day.attempts += 1;
+                        day.successes += u32::from(attempt.success);
+                        // Idle answers are left out, as they are from response times
+                        if !attempt.idle {
+                            day.practice_seconds += attempt.duration_ms / 1000.0;
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut daily: Vec<DailyRow> = daily.into_values().collect();
+        for day in &mut daily {
+            day.accuracy = ratio(day.successes, day.attempts);
+        }
+
+        let weakest = rank_weakest(&kana)
+            .iter()
+            .take(WEAKEST_COUNT)
+            .map(|row| row.kana.clone())
+            .collect();
+
+        Self {
+            profile: profile.to_string(),
+            exercise: filter.exercise,
+            kana,
+            weakest,
+            daily,
+            goal: None,
+        }
+    }
+
+    /// Adds the daily goal, streak and practice calendar to the report.
+    pub fn with_goal(mut self, history: &UserHistory, goal: DailyGoal) -> Self {
+        let today = Local::now().date_naive();
+        let days = daily_totals(&history.sessions);
+        let today_totals = days.get(&today).copied().unwrap_or_default();
+        self.goal = Some(GoalSummary {
+            goal,
+            goal_progress: goal.progress(&today_totals),
+            streak: streak(&days, goal, today),
+            status: goal.status(&today_totals),
+            calendar: Calendar::new(&days, goal, today).to_lines(CALENDAR_WEEKS),
+        });
+        self
+    }
+
+    /// Renders the report. CSV covers one table at a time: the per-kana rows,
+    /// or the daily totals when `daily` is set.
+    pub fn render(&self, format: ReportFormat, daily: bool) -> Result<String> {
+        match format {
+            ReportFormat::Json => Ok(serde_json::to_string_pretty(self)? + "\n"),
+            ReportFormat::Csv if daily => Ok(self.daily_csv()),
+            ReportFormat::Csv => Ok(self.kana_csv()),
+            ReportFormat::Table => Ok(self.table()),
+        }
+    }
+
+    fn kana_csv(&self) -> String {
+        let mut out = String::from(
+            "kana,romaji,kana_type,set,attempts,successes,failures,accuracy,ema_accuracy,ema_response_ms,last_seen,due\n",
+        );
+        for row in &self.kana {
+            let _ = writeln!(
+                out,
+                "{},{},{},{},{},{},{},{:.4},{:.4},{:.1},{},{}",
+                row.kana,
+                row.romaji,
+                row.kana_type,
+                row.set,
+                row.attempts,
+                row.successes,
+                row.failures,
+                row.accuracy,
+                row.ema_accuracy,
+                row.ema_response_ms,
+                row.last_seen.to_rfc3339(),
+                row.due.map(|due| due.to_rfc3339()).unwrap_or_default(),
+            );
+        }
+        out
+    }
+
+    fn daily_csv(&self) -> String {
+        let mut out = String::from("date,attempts,successes,accuracy,practice_seconds\n");
+        for day in &self.daily {
+            let _ = writeln!(
+                out,
+                "{},{},{},{:.4},{:.1}",
+                day.date, day.attempts, day.successes, day.accuracy, day.practice_seconds
+            );
+        }
+        out
+    }
+
+    fn table(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "Profile: {}  Exercise: {}", self.profile, self.exercise);
+
+        if let Some(goal) = &self.goal {
+            let _ = writeln!(
+                out,
+                "\nDaily goal: {}  Today: {}{}  Streak: {}\n",
+                goal.goal,
+                goal.status,
+                if goal.streak.met_today { " ✓" } else { "" },
+                goal.streak,
+            );
+            for line in &goal.calendar {
+                let _ = writeln!(out, "{}", line);
+            }
+            let _ = writeln!(out, "{}", LEGEND);
+        }
+
+        if self.kana.is_empty() {
+            out.push_str("\nNo attempts recorded yet.\n");
+            return out;
+        }
+
+        let _ = writeln!(
+            out,
+            "\n{}  {:<6} {:<9} {:<11} {:>8} {:>8} {:>8} {:>10}",
+            pad_kana("Kana", 4), "Romaji", "Type", "Set", "Attempts", "Accuracy", "EMA acc", "EMA resp"
+        );
+        for row in &self.kana {
+            let _ = writeln!(
+                out,
+                "{}  {:<6} {:<9} {:<11} {:>8} {:>7.1}% {:>7.1}% {:>8.0}ms",
+                pad_kana(&row.kana, 4),
+                row.romaji,
+                row.kana_type.to_string(),
+                row.set.to_string(),
+                row.attempts,
+                row.accuracy * 100.0,
+                row.ema_accuracy * 100.0,
+                row.ema_response_ms,
+            );
+        }
+
+        let _ = writeln!(out, "\nWeakest: {}", self.weakest.join(" "));
+
+        let _ = writeln!(out, "\n{:<10} {:>8} {:>8} {:>9}", "Date", "Attempts", "Accuracy", "Time");
+        for day in &self.daily {
+            let _ = writeln!(
+                out,
+                "{:<10} {:>8} {:>7.1}% {:>8.0}s",
+                day.date.to_string(),
+                day.attempts,
+                day.accuracy * 100.0,
+                day.practice_seconds,
+            );
+        }
+        out
+    }
+}
+
+/// Orders rows weakest first: lowest recent accuracy, then slowest recent response.
+pub fn rank_weakest(rows: &[KanaRow]) -> Vec<&KanaRow> {
+    let mut ranked: Vec<&KanaRow> = rows.iter().collect();
+    ranked.sort_by(|a, b| {
+        (a.ema_accuracy, -a.ema_response_ms)
+            .partial_cmp(&(b.ema_accuracy, -b.ema_response_ms))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    ranked
+}
+
+// Kana take two terminal columns each, so `{:<n}` would misalign them
+fn pad_kana(text: &str, width: usize) -> String {
+    let columns: usize = text.chars().map(|c| if c.is_ascii() { 1 } else { 2 }).sum();
+    format!("{}{}", text, " ".repeat(width.saturating_sub(columns)))
+}
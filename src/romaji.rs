@@ -0,0 +1,344 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::kana::*;
+use crate::types::{KanaType, Romanization};
+
+// Small kana that only appear inside words or are typed explicitly with an x/l prefix
+const SMALL_KANA: &[(&str, &str, &str)] = &[
+    ("xa", "ぁ", "ァ"), ("xi", "ぃ", "ィ"), ("xu", "ぅ", "ゥ"), ("xe", "ぇ", "ェ"), ("xo", "ぉ", "ォ"),
+    ("la", "ぁ", "ァ"), ("li", "ぃ", "ィ"), ("lu", "ぅ", "ゥ"), ("le", "ぇ", "ェ"), ("lo", "ぉ", "ォ"),
+    ("xya", "ゃ", "ャ"), ("xyu", "ゅ", "ュ"), ("xyo", "ょ", "ョ"),
+    ("lya", "ゃ", "ャ"), ("lyu", "ゅ", "ュ"), ("lyo", "ょ", "ョ"),
+    ("xtu", "っ", "ッ"), ("ltu", "っ", "ッ"), ("xtsu", "っ", "ッ"), ("ltsu", "っ", "ッ"),
+    ("-", "ー", "ー"),
+];
+
+const MAX_ROMAJI_LEN: usize = 4;
+
+fn romaji_table(kana_type: KanaType) -> &'static HashMap<&'static str, &'static str> {
+    static HIRAGANA: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+    static KATAKANA: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+
+    let (cell, kana_set): (_, &[KanaEntry]) = match kana_type {
+        KanaType::Hiragana => (&HIRAGANA, &ALL_HIRAGANA),
+        KanaType::Katakana => (&KATAKANA, &ALL_KATAKANA),
+    };
+
+    cell.get_or_init(|| {
+        let mut table = HashMap::new();
+        // Earlier entries win, so じ takes "ji" before ぢ and を takes "wo" before ウォ
+        for entry in kana_set {
+            for romaji in entry.readings(Romanization::Any) {
+                table.entry(romaji).or_insert(entry.kana);
+            }
+        }
+        for &(romaji, hiragana, katakana) in SMALL_KANA {
+            let kana = match kana_type {
+                KanaType::Hiragana => hiragana,
+                KanaType::Katakana => katakana,
+            };
+            table.entry(romaji).or_insert(kana);
+        }
+        table
+    })
+}
+
+fn is_vowel(c: char) -> bool {
+    matches!(c, 'a' | 'i' | 'u' | 'e' | 'o')
+}
+
+/// Converts typed romaji to kana the way an IME would.
+///
+/// Accepts every supported romanization. Doubled consonants become a small
+/// tsu, and characters that cannot be converted yet (such as a trailing "k")
+/// are kept as-is so the caller can show them as pending input.
+pub fn to_kana(input: &str, kana_type: KanaType) -> String {
+    let table = romaji_table(kana_type);
+    let (sokuon, n) = match kana_type {
+        KanaType::Hiragana => ("っ", "ん"),
+        KanaType::Katakana => ("ッ", "ン"),
+    };
+
+    let input = input.to_lowercase();
+    let chars: Vec<char> = input.chars().collect();
+    let mut output = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+
+        if c == 'n' && next == Some('\'') {
+            output.push_str(n);
+            i += 2;
+            continue;
+        }
+
+        if c.is_ascii_alphabetic() && !is_vowel(c) && c != 'n' && next == Some(c) {
+            output.push_str(sokuon);
+            i += 1;
+            continue;
+        }
+
+        let matched = (1..=MAX_ROMAJI_LEN.min(chars.len() - i)).rev().find_map(|len| {
+            let candidate: String = chars[i..i + len].iter().collect();
+            table.get(candidate.as_str()).map(|kana| (kana, len))
+        });
+
+        if let Some((kana, len)) = matched {
+            output.push_str(kana);
+            i += len;
+        } else {
+            output.push(c);
+            i += 1;
+        }
+    }
+
+    output
+}
+
+/// The romaji shown for a kana in reverse practice.
+///
+/// Picks the first accepted reading that converts back to this kana, so
+/// homophones stay answerable: ぢ is prompted as "di" because "ji" writes じ.
+pub fn reverse_prompt(entry: &KanaEntry, kana_type: KanaType, policy: Romanization) -> &'static str {
+    entry
+        .readings(policy)
+        .into_iter()
+        .chain(entry.readings(Romanization::Any))
+        .find(|romaji| to_kana(romaji, kana_type) == entry.kana)
+        .unwrap_or_else(|| entry.romaji(policy))
+}
+
+/// One mora of a word with the spellings accepted for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mora {
+    /// The kana as written, including a leading っ when the mora is geminated.
+    pub text: String,
+    /// The kana whose statistics this mora counts towards, if it is a practised kana.
+    pub entry: Option<&'static KanaEntry>,
+    /// Accepted spellings, canonical first.
+    pub readings: Vec<String>,
+}
+
+fn is_sokuon(c: char) -> bool {
+    c == 'っ' || c == 'ッ'
+}
+
+fn push_unique(readings: &mut Vec<String>, reading: String) {
+    if !readings.contains(&reading) {
+        readings.push(reading);
+    }
+}
+
+/// Spellings of a mora that follows っ: the leading consonant is doubled.
+fn geminate(readings: &[String], policy: Romanization) -> Vec<String> {
+    let mut doubled = Vec::new();
+    for reading in readings {
+        let Some(first) = reading.chars().next().filter(|c| !is_vowel(*c)) else {
+            push_unique(&mut doubled, format!("xtu{}", reading));
+            continue;
+        };
+        // Hepburn writes っち as "tchi"; IMEs also take "cchi"
+        if reading.starts_with("ch") && policy != Romanization::Kunrei && policy != Romanization::Nihon {
+            push_unique(&mut doubled, format!("t{}", reading));
+            if policy == Romanization::Any {
+                push_unique(&mut doubled, format!("c{}", reading));
+            }
+        } else {
+            push_unique(&mut doubled, format!("{}{}", first, reading));
+        }
+    }
+    if policy == Romanization::Any {
+        for reading in readings {
+            for prefix in ["xtu", "ltu", "xtsu"] {
+                push_unique(&mut doubled, format!("{}{}", prefix, reading));
+            }
+        }
+    }
+    doubled
+}
+
+/// Splits a word into morae and works out the romaji accepted for each one.
+///
+/// Handles っ gemination, the ー long-vowel mark (spelled by repeating the
+/// previous vowel, or "-" under the `Any` policy) and ん before a vowel or y,
+/// which needs "n'" to stay unambiguous.
+pub fn transliterate(word: &str, policy: Romanization) -> Vec<Mora> {
+    let chars: Vec<char> = word.chars().collect();
+    let mut morae: Vec<Mora> = Vec::new();
+    let mut sokuon: Option<char> = None;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if is_sokuon(c) && i + 1 < chars.len() && !is_sokuon(chars[i + 1]) {
+            sokuon = Some(c);
+            i += 1;
+            continue;
+        }
+
+        if c == 'ー' {
+            let vowel = morae
+                .last()
+                .and_then(|mora| mora.readings.first())
+                .and_then(|reading| reading.chars().last())
+                .filter(|c| is_vowel(*c));
+            let mut readings: Vec<String> = vowel.map(String::from).into_iter().collect();
+            if policy == Romanization::Any || readings.is_empty() {
+                push_unique(&mut readings, "-".to_string());
+            }
+            morae.push(Mora { text: c.to_string(), entry: None, readings });
+            i += 1;
+            continue;
+        }
+
+        // Prefer two-character combinations such as きゃ over き + ゃ
+        let (entry, len) = match chars.get(i..i + 2).map(|pair| pair.iter().collect::<String>()) {
+            Some(pair) if lookup_entry(&pair).is_some() => (lookup_entry(&pair), 2),
+            _ => (lookup_entry(&c.to_string()), 1),
+        };
+
+        let kana: String = chars[i..i + len].iter().collect();
+        let mut readings: Vec<String> = match entry {
+            Some(entry) => entry.readings(policy).into_iter().map(String::from).collect(),
+            None => vec![to_romaji_fallback(&kana)],
+        };
+
+        let text = match sokuon.take() {
+            Some(sokuon) => {
+                readings = geminate(&readings, policy);
+                format!("{}{}", sokuon, kana)
+            }
+            None => kana,
+        };
+
+        morae.push(Mora { text, entry, readings });
+        i += len;
+    }
+
+    // ん followed by a vowel or y must be written "n'" so it isn't read as な行
+    for idx in 0..morae.len().saturating_sub(1) {
+        let is_n = morae[idx].entry.is_some_and(|entry| entry.hepburn == "n");
+        let next_is_ambiguous = morae[idx + 1]
+            .readings
+            .first()
+            .and_then(|reading| reading.chars().next())
+            .is_some_and(|c| is_vowel(c) || c == 'y');
+        if is_n && next_is_ambiguous {
+            let mut readings = vec!["n'".to_string()];
+            if policy == Romanization::Any {
+                push_unique(&mut readings, "nn".to_string());
+            }
+            morae[idx].readings = readings;
+        }
+    }
+
+    morae
+}
+
+// Small kana and stray sokuon have no entry of their own; spell them the IME way
+fn to_romaji_fallback(text: &str) -> String {
+    SMALL_KANA
+        .iter()
+        .find(|&&(_, hiragana, katakana)| hiragana == text || katakana == text)
+        .map(|&(romaji, _, _)| romaji.to_string())
+        .unwrap_or_else(|| text.to_string())
+}
+
+/// The canonical romaji spelling of a word under the given policy.
+pub fn to_romaji(word: &str, policy: Romanization) -> String {
+    transliterate(word, policy)
+        .iter()
+        .filter_map(|mora| mora.readings.first())
+        .map(String::as_str)
+        .collect()
+}
+
+/// Whether the input spells every mora with one of its accepted readings.
+pub fn spells(morae: &[Mora], input: &str) -> bool {
+    match morae.split_first() {
+        None => input.is_empty(),
+        Some((mora, rest)) => mora.readings.iter().any(|reading| {
+            input
+                .strip_prefix(reading.as_str())
+                .is_some_and(|remaining| spells(rest, remaining))
+        }),
+    }
+}
+
+/// Lines the input up against each mora and reports which ones were typed correctly.
+///
+/// Each mora takes its longest matching reading. On a mismatch, the typed
+/// segment is sized so that the next mora lines up again if possible,
+/// falling back to the length of the canonical reading.
+pub fn align<'a>(morae: &[Mora], input: &'a str) -> Vec<(bool, &'a str)> {
+    let longest_match = |mora: &Mora, rest: &str| {
+        mora.readings
+            .iter()
+            .filter(|reading| rest.starts_with(reading.as_str()))
+            .map(String::len)
+            .max()
+    };
+
+    let mut rest = input;
+    let mut results = Vec::with_capacity(morae.len());
+
+    for (idx, mora) in morae.iter().enumerate() {
+        let (matched, len) = match longest_match(mora, rest) {
+            Some(len) => (true, len),
+            None => {
+                let canonical = mora.readings.first().map_or(0, String::len);
+                let resyncs = |len: &usize| {
+                    rest.get(*len..)
+                        .zip(morae.get(idx + 1))
+                        .is_some_and(|(after, next)| longest_match(next, after).is_some())
+                };
+                let len = std::iter::once(canonical)
+                    .chain(1..=canonical + 1)
+                    .find(resyncs)
+                    .unwrap_or(canonical);
+                (false, len)
+            }
+        };
+
+        let split = (0..=len.min(rest.len()))
+            .rev()
+            .find(|&idx| rest.is_char_boundary(idx))
+            .unwrap_or(0);
+        let (typed, remaining) = rest.split_at(split);
+        results.push((matched, typed));
+        rest = remaining;
+    }
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::PracticeMode;
+
+    #[test]
+    fn every_kana_is_answerable_in_reverse_mode() {
+        let policies = [Romanization::Hepburn, Romanization::Kunrei, Romanization::Nihon, Romanization::Any];
+        let modes = [PracticeMode::Main, PracticeMode::Dakuten, PracticeMode::Combination, PracticeMode::All];
+        for kana_type in [KanaType::Hiragana, KanaType::Katakana] {
+            for mode in modes {
+                for entry in kana_set(kana_type, mode) {
+                    for policy in policies {
+                        let prompt = reverse_prompt(entry, kana_type, policy);
+                        assert_eq!(
+                            to_kana(prompt, kana_type),
+                            entry.kana,
+                            "{:?} {:?} prompt {:?} under {}",
+                            kana_type, mode, prompt, policy
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
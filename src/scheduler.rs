@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+
+use crate::kana::KanaEntry;
+use crate::types::{CharacterStats, SchedulerKind};
+use chrono::{DateTime, Utc};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+use tracing::debug;
+
+/// Chooses which kana to show next.
+///
+/// `candidates` is the practice set after any due-only filtering and is never
+/// empty. `stats` holds the statistics for the current exercise and has an
+/// entry for every candidate. Implementations return an index into `candidates`.
+pub trait Scheduler {
+    fn next(
+        &mut self,
+        candidates: &[&'static KanaEntry],
+        stats: &HashMap<String, CharacterStats>,
+        now: DateTime<Utc>,
+        rng: &mut dyn RngCore,
+    ) -> Option<usize>;
+}
+
+pub fn make_scheduler(kind: SchedulerKind) -> Box<dyn Scheduler> {
+    match kind {
+        SchedulerKind::Weighted => Box::new(WeightedScheduler),
+        SchedulerKind::Sm2 => Box::new(Sm2Scheduler::default()),
+        SchedulerKind::Uniform => Box::new(UniformScheduler),
+        SchedulerKind::Deck => Box::new(DeckScheduler::default()),
+        SchedulerKind::Weakest => Box::new(WeakestFirstScheduler::default()),
+    }
+}
+
+/// Random selection weighted by `CharacterStats::calculate_weight`.
+pub struct WeightedScheduler;
+
+impl Scheduler for WeightedScheduler {
+    fn next(
+        &mut self,
+        candidates: &[&'static KanaEntry],
+        stats: &HashMap<String, CharacterStats>,
+        now: DateTime<Utc>,
+        rng: &mut dyn RngCore,
+    ) -> Option<usize> {
+        let weights: Vec<f64> = candidates
+            .iter()
+            .map(|entry| stats.get(entry.kana).map_or(3.0, |stats| stats.calculate_weight(now)))
+            .collect();
+
+        let min_weight = weights.iter()
+            .copied()
+            .fold(f64::INFINITY, f64::min);
+        let max_weight = weights.iter()
+            .copied()
+            .fold(f64::NEG_INFINITY, f64::max);
+        let range = max_weight - min_weight;
+
+        // Normalize weights to range [1, 10] to ensure positive weights
+        let normalized_weights: Vec<f64> = if range > 0.0 {
+            weights.iter().map(|w| {
+                1.0 + 9.0 * (w - min_weight) / range
+            }).collect()
+        } else {
+            vec![1.0; weights.len()]
+        };
+
+        let dist = WeightedIndex::new(&normalized_weights).ok()?;
+        let selected = dist.sample(rng);
+
+        debug!(
+            candidates = candidates.len(),
+            min_weight = min_weight,
+            max_weight = max_weight,
+            weights_range = range,
+            normalized_weights = ?normalized_weights,
+            selected_weight = weights[selected],
+            "Weighted kana selection"
+        );
+
+        Some(selected)
+    }
+}
+
+/// Reviews the most overdue kana first, then introduces unseen ones.
+/// Falls back to weighted selection when nothing is due.
+#[derive(Default)]
+pub struct Sm2Scheduler {
+    last: Option<&'static str>,
+}
+
+impl Scheduler for Sm2Scheduler {
+    fn next(
+        &mut self,
+        candidates: &[&'static KanaEntry],
+        stats: &HashMap<String, CharacterStats>,
+        now: DateTime<Utc>,
+        rng: &mut dyn RngCore,
+    ) -> Option<usize> {
+        let due: Vec<usize> = (0..candidates.len())
+            .filter(|&idx| stats.get(candidates[idx].kana).is_none_or(|stats| stats.is_due(now)))
+            .collect();
+
+        let not_last: Vec<usize> = due
+            .iter()
+            .copied()
+            .filter(|&idx| Some(candidates[idx].kana) != self.last)
+            .collect();
+        let pool = if not_last.is_empty() { due } else { not_last };
+
+        let selected = pool
+            .into_iter()
+            .min_by_key(|&idx| {
+                let due = stats.get(candidates[idx].kana).and_then(|stats| stats.due);
+                (due.is_none(), due)
+            })
+            .or_else(|| WeightedScheduler.next(candidates, stats, now, rng))?;
+
+        self.last = Some(candidates[selected].kana);
+        Some(selected)
+    }
+}
+
+/// Every candidate is equally likely.
+pub struct UniformScheduler;
+
+impl Scheduler for UniformScheduler {
+    fn next(
+        &mut self,
+        candidates: &[&'static KanaEntry],
+        _stats: &HashMap<String, CharacterStats>,
+        _now: DateTime<Utc>,
+        rng: &mut dyn RngCore,
+    ) -> Option<usize> {
+        if candidates.is_empty() {
+            return None;
+        }
+        Some(rng.gen_range(0..candidates.len()))
+    }
+}
+
+/// Deals the candidates like a shuffled deck, so each kana appears once per round.
+#[derive(Default)]
+pub struct DeckScheduler {
+    deck: Vec<&'static str>,
+}
+
+impl Scheduler for DeckScheduler {
+    fn next(
+        &mut self,
+        candidates: &[&'static KanaEntry],
+        _stats: &HashMap<String, CharacterStats>,
+        _now: DateTime<Utc>,
+        rng: &mut dyn RngCore,
+    ) -> Option<usize> {
+        // Drop cards that left the candidate set, e.g. after switching practice set
+        self.deck.retain(|kana| candidates.iter().any(|entry| entry.kana == *kana));
+
+        if self.deck.is_empty() {
+            self.deck = candidates.iter().map(|entry| entry.kana).collect();
+            self.deck.shuffle(rng);
+        }
+
+        let kana = self.deck.pop()?;
+        candidates.iter().position(|entry| entry.kana == kana)
+    }
+}
+
+/// Always shows the kana with the lowest recent accuracy, slowest first on ties.
+/// Unseen kana count as the weakest.
+#[derive(Default)]
+pub struct WeakestFirstScheduler {
+    last: Option<&'static str>,
+}
+
+impl Scheduler for WeakestFirstScheduler {
+    fn next(
+        &mut self,
+        candidates: &[&'static KanaEntry],
+        stats: &HashMap<String, CharacterStats>,
+        _now: DateTime<Utc>,
+        _rng: &mut dyn RngCore,
+    ) -> Option<usize> {
+        let strength = |idx: &usize| match stats.get(candidates[*idx].kana) {
+            Some(stats) if stats.appearances > 0 => (stats.exp_avg_accuracy, -stats.exp_avg_response),
+            _ => (f64::NEG_INFINITY, 0.0),
+        };
+
+        let selected = (0..candidates.len())
+            .filter(|&idx| candidates.len() == 1 || Some(candidates[idx].kana) != self.last)
+            .min_by(|a, b| strength(a).partial_cmp(&strength(b)).unwrap_or(std::cmp::Ordering::Equal))?;
+
+        self.last = Some(candidates[selected].kana);
+        Some(selected)
+    }
+}
@@ -0,0 +1,494 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::Result;
+use crate::storage::{self, Storage};
+use crate::types::{CharacterStats, Keystroke, MistakeEntry, SessionRecord, TestEntry, UserHistory};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde::de::DeserializeOwned;
+use tracing::{debug, info, warn};
+
+/// Layout of the database, stored in `PRAGMA user_version`. Bump it together
+/// with a new step in `UPGRADES` whenever the tables change.
+const SCHEMA_VERSION: u32 = 5;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS meta (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS stats (
+        stats_set TEXT NOT NULL,
+        kana TEXT NOT NULL,
+        appearances INTEGER NOT NULL,
+        successes INTEGER NOT NULL,
+        failures INTEGER NOT NULL,
+        total_response_time REAL NOT NULL,
+        last_appearance TEXT NOT NULL,
+        exp_avg_response REAL NOT NULL,
+        exp_avg_accuracy REAL NOT NULL,
+        exp_avg_first_key REAL,
+        ease REAL NOT NULL,
+        interval_days REAL NOT NULL,
+        repetitions INTEGER NOT NULL,
+        due TEXT,
+        PRIMARY KEY (stats_set, kana)
+    );
+    CREATE TABLE IF NOT EXISTS attempts (
+        id INTEGER PRIMARY KEY,
+        stats_set TEXT NOT NULL,
+        kana TEXT NOT NULL,
+        input TEXT NOT NULL,
+        start_time TEXT NOT NULL,
+        duration_ms REAL NOT NULL,
+        success INTEGER NOT NULL,
+        hints INTEGER NOT NULL DEFAULT 0,
+        keystrokes TEXT NOT NULL DEFAULT '',
+        idle INTEGER NOT NULL DEFAULT 0
+    );
+    CREATE INDEX IF NOT EXISTS attempts_by_kana ON attempts (stats_set, kana, id);
+    CREATE TABLE IF NOT EXISTS mistakes (
+        id INTEGER PRIMARY KEY,
+        stats_set TEXT NOT NULL,
+        kana TEXT NOT NULL,
+        input TEXT NOT NULL,
+        timestamp TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS mistakes_by_kana ON mistakes (stats_set, kana, id);
+    CREATE TABLE IF NOT EXISTS sessions (
+        start TEXT PRIMARY KEY,
+        end TEXT NOT NULL,
+        active_seconds REAL NOT NULL,
+        attempts INTEGER NOT NULL,
+        successes INTEGER NOT NULL,
+        kana_type TEXT NOT NULL,
+        practice_mode TEXT NOT NULL,
+        exercise TEXT NOT NULL
+    );
+";
+
+/// Brings a database from version `n + 1` to `n + 2`. New databases get
+/// `SCHEMA`, which always has the latest layout.
+const UPGRADES: [&str; 4] = [
+    "ALTER TABLE attempts ADD COLUMN hints INTEGER NOT NULL DEFAULT 0;",
+    "ALTER TABLE attempts ADD COLUMN keystrokes TEXT NOT NULL DEFAULT '';
+     ALTER TABLE stats ADD COLUMN exp_avg_first_key REAL;",
+    // Tags the slow attempts the same way as the JSON migration to schema 2
+    "ALTER TABLE attempts ADD COLUMN idle INTEGER NOT NULL DEFAULT 0;
+     UPDATE attempts SET idle = 1 WHERE duration_ms > 30000;",
+    "CREATE TABLE IF NOT EXISTS sessions (
+        start TEXT PRIMARY KEY,
+        end TEXT NOT NULL,
+        active_seconds REAL NOT NULL,
+        attempts INTEGER NOT NULL,
+        successes INTEGER NOT NULL,
+        kana_type TEXT NOT NULL,
+        practice_mode TEXT NOT NULL,
+        exercise TEXT NOT NULL
+    );",
+];
+
+// The `stats_set` column holds the name of the `UserHistory` field a row belongs to
+const CHARACTER_STATS: &str = "character_stats";
+const REVERSE_STATS: &str = "reverse_stats";
+
+/// What the database already holds for one kana, so a save only has to
+/// append the attempts made since.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SavedRows {
+    attempts: usize,
+    mistakes: usize,
+    last_attempt: Option<DateTime<Utc>>,
+    last_mistake: Option<DateTime<Utc>>,
+}
+
+impl SavedRows {
+    fn of(stats: &CharacterStats) -> Self {
+        Self {
+            attempts: stats.test_history.len(),
+            mistakes: stats.mistakes.len(),
+            last_attempt: stats.test_history.last().map(|entry| entry.start_time),
+            last_mistake: stats.mistakes.last().map(|entry| entry.timestamp),
+        }
+    }
+
+    // Whether `stats` only grew since this was saved, so its new rows can be appended
+    fn is_prefix_of(&self, stats: &CharacterStats) -> bool {
+        let attempt_at = |n: usize| n.checked_sub(1).map(|idx| stats.test_history[idx].start_time);
+        let mistake_at = |n: usize| n.checked_sub(1).map(|idx| stats.mistakes[idx].timestamp);
+        self.attempts <= stats.test_history.len()
+            && self.mistakes <= stats.mistakes.len()
+            && attempt_at(self.attempts) == self.last_attempt
+            && mistake_at(self.mistakes) == self.last_mistake
+    }
+}
+
+/// Keeps the history in a SQLite database with one row per attempt.
+///
+/// Saves run in a single transaction and only insert the attempts made since
+/// the last load or save, so their cost doesn't grow with the history.
+pub struct SqliteStore {
+    path: PathBuf,
+    conn: Option<Connection>,
+    // None until the database contents are known, i.e. before the first load or save
+    saved: Option<HashMap<(&'static str, String), SavedRows>>,
+}
+
+impl SqliteStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            conn: None,
+            saved: None,
+        }
+    }
+
+    fn connection(&mut self) -> Result<&mut Connection> {
+        if self.conn.is_none() {
+            if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+                fs::create_dir_all(dir)?;
+            }
+            let conn = Connection::open(&self.path)?;
+            create_or_upgrade(&self.path, &conn)?;
+            self.conn = Some(conn);
+        }
+        Ok(self.conn.as_mut().expect("connection was just opened"))
+    }
+}
+
+fn create_or_upgrade(path: &Path, conn: &Connection) -> Result<()> {
+    let version: u32 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    storage::check_not_newer(path, version, SCHEMA_VERSION)?;
+
+    if version < SCHEMA_VERSION {
+        if version == 0 {
+            conn.execute_batch(SCHEMA)?;
+        } else {
+            for step in &UPGRADES[version as usize - 1..] {
+                conn.execute_batch(step)?;
+            }
+        }
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        info!(path = %path.display(), from = version, to = SCHEMA_VERSION, "Prepared history database");
+    }
+    Ok(())
+}
+
+fn read_meta<T: std::str::FromStr>(conn: &Connection, key: &str) -> Result<Option<T>> {
+    let value: Option<String> = conn
+        .query_row("SELECT value FROM meta WHERE key = ?1", [key], |row| row.get(0))
+        .optional()?;
+    Ok(value.and_then(|value| value.parse().ok()))
+}
+
+fn load_stats(conn: &Connection, stats_set: &str) -> Result<HashMap<String, CharacterStats>> {
+    let mut stats_by_kana = HashMap::new();
+
+    let mut stmt = conn.prepare(
+        "SELECT kana, appearances, successes, failures, total_response_time, last_appearance,
+                exp_avg_response, exp_avg_accuracy, ease, interval_days, repetitions, due,
+                exp_avg_first_key
+         FROM stats WHERE stats_set = ?1",
+    )?;
+    let rows = stmt.query_map([stats_set], |row| {
+        let mut stats = CharacterStats::new();
+        stats.appearances = row.get(1)?;
+        stats.successes = row.get(2)?;
+        stats.failures = row.get(3)?;
+        stats.total_response_time = row.get(4)?;
+        stats.last_appearance = row.get(5)?;
+        stats.exp_avg_response = row.get(6)?;
+        stats.exp_avg_accuracy = row.get(7)?;
+        stats.ease = row.get(8)?;
+        stats.interval_days = row.get(9)?;
+        stats.repetitions = row.get(10)?;
+        stats.due = row.get(11)?;
+        stats.exp_avg_first_key = row.get(12)?;
+        Ok((row.get::<_, String>(0)?, stats))
+    })?;
+    for row in rows {
+        let (kana, stats) = row?;
+        stats_by_kana.insert(kana, stats);
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT kana, input, start_time, duration_ms, success, hints, keystrokes, idle
+         FROM attempts WHERE stats_set = ?1 ORDER BY id",
+    )?;
+    let rows = stmt.query_map([stats_set], |row| {
+        let entry = TestEntry {
+            input: row.get(1)?,
+            start_time: row.get(2)?,
+            duration_ms: row.get(3)?,
+            success: row.get(4)?,
+            hints: row.get(5)?,
+            keystrokes: Vec::new(),
+            idle: row.get(7)?,
+        };
+        Ok((row.get::<_, String>(0)?, entry, row.get::<_, String>(6)?))
+    })?;
+    for row in rows {
+        let (kana, mut entry, keystrokes) = row?;
+        entry.keystrokes = Keystroke::decode(&keystrokes).unwrap_or_else(|| {
+            warn!(kana = kana, keystrokes = keystrokes, "Unreadable keystrokes, dropping them");
+            Vec::new()
+        });
+        match stats_by_kana.get_mut(&kana) {
+            Some(stats) => stats.test_history.push(entry),
+            None => warn!(kana = kana, stats_set = stats_set, "Attempt without statistics, skipping"),
+        }
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT kana, input, timestamp FROM mistakes WHERE stats_set = ?1 ORDER BY id",
+    )?;
+    let rows = stmt.query_map([stats_set], |row| {
+        let entry = MistakeEntry {
+            input: row.get(1)?,
+            timestamp: row.get(2)?,
+        };
+        Ok((row.get::<_, String>(0)?, entry))
+    })?;
+    for row in rows {
+        let (kana, entry) = row?;
+        if let Some(stats) = stats_by_kana.get_mut(&kana) {
+            stats.mistakes.push(entry);
+        }
+    }
+
+    Ok(stats_by_kana)
+}
+
+// Enums are stored under their serde names, which are also how they display
+fn parse_name<T: DeserializeOwned>(name: String) -> Result<T> {
+    Ok(serde_json::from_value(serde_json::Value::String(name))?)
+}
+
+fn load_sessions(conn: &Connection) -> Result<Vec<SessionRecord>> {
+    let mut stmt = conn.prepare(
+        "SELECT start, end, active_seconds, attempts, successes, kana_type, practice_mode, exercise
+         FROM sessions ORDER BY start",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, DateTime<Utc>>(0)?,
+            row.get::<_, DateTime<Utc>>(1)?,
+            row.get::<_, f64>(2)?,
+            row.get::<_, u32>(3)?,
+            row.get::<_, u32>(4)?,
+            row.get::<_, String>(5)?,
+            row.get::<_, String>(6)?,
+            row.get::<_, String>(7)?,
+        ))
+    })?;
+
+    let mut sessions = Vec::new();
+    for row in rows {
+        let (start, end, active_seconds, attempts, successes, kana_type, practice_mode, exercise) = row?;
+        sessions.push(SessionRecord {
+            start,
+            end,
+            active_seconds,
+            attempts,
+            successes,
+            kana_type: parse_name(kana_type)?,
+            practice_mode: parse_name(practice_mode)?,
+            exercise: parse_name(exercise)?,
+        });
+    }
+    Ok(sessions)
+}
+
+// Sessions only ever get added, so rows that are already there are left alone
+fn save_sessions(tx: &Transaction<'_>, sessions: &[SessionRecord]) -> Result<()> {
+    let mut insert = tx.prepare_cached(
+        "INSERT OR IGNORE INTO sessions (start, end, active_seconds, attempts, successes,
+             kana_type, practice_mode, exercise)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+    )?;
+    for session in sessions {
+        insert.execute(params![
+            session.start,
+            session.end,
+            session.active_seconds,
+            session.attempts,
+            session.successes,
+            session.kana_type.to_string(),
+            session.practice_mode.to_string(),
+            session.exercise.to_string(),
+        ])?;
+    }
+    Ok(())
+}
+
+fn delete_kana(tx: &Transaction<'_>, stats_set: &str, kana: &str) -> Result<()> {
+    for table in ["stats", "attempts", "mistakes"] {
+        tx.execute(
+            &format!("DELETE FROM {} WHERE stats_set = ?1 AND kana = ?2", table),
+            params![stats_set, kana],
+        )?;
+    }
+    Ok(())
+}
+
+fn save_stats(
+    tx: &Transaction<'_>,
+    stats_set: &'static str,
+    stats_by_kana: &HashMap<String, CharacterStats>,
+    saved: &mut HashMap<(&'static str, String), SavedRows>,
+) -> Result<()> {
+    let mut upsert = tx.prepare_cached(
+        "INSERT OR REPLACE INTO stats (stats_set, kana, appearances, successes, failures,
+             total_response_time, last_appearance, exp_avg_response, exp_avg_accuracy,
+             ease, interval_days, repetitions, due, exp_avg_first_key)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+    )?;
+    let mut insert_attempt = tx.prepare_cached(
+        "INSERT INTO attempts (stats_set, kana, input, start_time, duration_ms, success, hints, keystrokes, idle)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+    )?;
+    let mut insert_mistake = tx.prepare_cached(
+        "INSERT INTO mistakes (stats_set, kana, input, timestamp) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+
+    for (kana, stats) in stats_by_kana {
+        let key = (stats_set, kana.clone());
+        let already_saved = match saved.get(&key) {
+            Some(rows) if rows.is_prefix_of(stats) => *rows,
+            Some(_) => {
+                // Earlier attempts changed, e.g. after a merge: rewrite this kana
+                debug!(kana = kana, stats_set = stats_set, "Rewriting attempts");
+                delete_kana(tx, stats_set, kana)?;
+                SavedRows::of(&CharacterStats::new())
+            }
+            None => SavedRows::of(&CharacterStats::new()),
+        };
+
+        upsert.execute(params![
+            stats_set,
+            kana,
+            stats.appearances,
+            stats.successes,
+            stats.failures,
+            stats.total_response_time,
+            stats.last_appearance,
+            stats.exp_avg_response,
+            stats.exp_avg_accuracy,
+            stats.ease,
+            stats.interval_days,
+            stats.repetitions,
+            stats.due,
+            stats.exp_avg_first_key,
+        ])?;
+        for entry in &stats.test_history[already_saved.attempts..] {
+            insert_attempt.execute(params![
+                stats_set,
+                kana,
+                entry.input,
+                entry.start_time,
+                entry.duration_ms,
+                entry.success,
+                entry.hints,
+                Keystroke::encode(&entry.keystrokes),
+                entry.idle,
+            ])?;
+        }
+        for entry in &stats.mistakes[already_saved.mistakes..] {
+            insert_mistake.execute(params![stats_set, kana, entry.input, entry.timestamp])?;
+        }
+
+        saved.insert(key, SavedRows::of(stats));
+    }
+
+    let removed: Vec<String> = saved
+        .keys()
+        .filter(|(set, kana)| *set == stats_set && !stats_by_kana.contains_key(kana))
+        .map(|(_, kana)| kana.clone())
+        .collect();
+    for kana in removed {
+        delete_kana(tx, stats_set, &kana)?;
+        saved.remove(&(stats_set, kana));
+    }
+
+    Ok(())
+}
+
+impl Storage for SqliteStore {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn load(&mut self) -> Result<UserHistory> {
+        if !self.path.exists() {
+            self.saved = Some(HashMap::new());
+            return Ok(UserHistory::default());
+        }
+
+        let conn = self.connection()?;
+        let mut history = UserHistory {
+            character_stats: load_stats(conn, CHARACTER_STATS)?,
+            reverse_stats: load_stats(conn, REVERSE_STATS)?,
+            sessions: load_sessions(conn)?,
+            ..UserHistory::default()
+        };
+        if let Some(last_session) = read_meta::<DateTime<Utc>>(conn, "last_session")? {
+            history.last_session = last_session;
+        }
+        if let Some(total) = read_meta::<f64>(conn, "total_practice_time")? {
+            history.total_practice_time = total;
+        }
+
+        let mut saved = HashMap::new();
+        for (stats_set, stats_by_kana) in [
+            (CHARACTER_STATS, &history.character_stats),
+            (REVERSE_STATS, &history.reverse_stats),
+        ] {
+            for (kana, stats) in stats_by_kana {
+                saved.insert((stats_set, kana.clone()), SavedRows::of(stats));
+            }
+        }
+        self.saved = Some(saved);
+
+        storage::finish_load(&mut history);
+        Ok(history)
+    }
+
+    fn save(&mut self, history: &UserHistory) -> Result<()> {
+        let path = self.path.clone();
+        let mut saved = self.saved.take();
+        let conn = self.connection()?;
+        // Another process may have upgraded the database since it was opened
+        create_or_upgrade(&path, conn)?;
+        let tx = conn.transaction()?;
+
+        let saved_rows = match saved.as_mut() {
+            Some(saved) => saved,
+            None => {
+                // Saving without loading first replaces whatever the database held
+                for table in ["stats", "attempts", "mistakes", "sessions"] {
+                    tx.execute(&format!("DELETE FROM {}", table), [])?;
+                }
+                saved.insert(HashMap::new())
+            }
+        };
+
+        save_stats(&tx, CHARACTER_STATS, &history.character_stats, saved_rows)?;
+        save_stats(&tx, REVERSE_STATS, &history.reverse_stats, saved_rows)?;
+        save_sessions(&tx, &history.sessions)?;
+        for (key, value) in [
+            ("last_session", history.last_session.to_rfc3339()),
+            ("total_practice_time", history.total_practice_time.to_string()),
+        ] {
+            tx.execute(
+                "INSERT OR REPLACE INTO meta (key, value) VALUES (?1, ?2)",
+                params![key, value],
+            )?;
+        }
+
+        tx.commit()?;
+        self.saved = saved;
+        Ok(())
+    }
+}
@@ -0,0 +1,287 @@
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::error::{KanaError, Result};
+use crate::migrations::{self, CURRENT_SCHEMA_VERSION};
+use crate::paths;
+use crate::sqlite::SqliteStore;
+use crate::types::{StorageKind, UserHistory};
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::{info, warn};
+
+const BACKUP_COUNT: usize = 3;
+
+/// A place the history can be loaded from and saved to.
+pub trait Storage {
+    fn path(&self) -> &Path;
+
+    /// Loads the history, or an empty one if nothing has been saved yet.
+    fn load(&mut self) -> Result<UserHistory>;
+
+    fn save(&mut self, history: &UserHistory) -> Result<()>;
+}
+
+/// The backend to use when none is given: SQLite once a database exists, JSON otherwise.
+pub fn detect(data_dir: &Path) -> StorageKind {
+    if paths::database_path(data_dir).exists() {
+        StorageKind::Sqlite
+    } else {
+        StorageKind::Json
+    }
+}
+
+/// Opens a history file by path, as SQLite for `.db` files and JSON otherwise.
+pub fn open_file(path: &Path) -> Box<dyn Storage> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("db") => Box::new(SqliteStore::new(path.to_path_buf())),
+        _ => Box::new(JsonStore::new(path.to_path_buf())),
+    }
+}
+
+pub fn open(kind: StorageKind, data_dir: &Path) -> Box<dyn Storage> {
+    match kind {
+        StorageKind::Json => Box::new(JsonStore::new(paths::history_path(data_dir))),
+        StorageKind::Sqlite => Box::new(SqliteStore::new(paths::database_path(data_dir))),
+    }
+}
+
+/// Copies the history in `data_dir` into the `to` backend.
+///
+/// The source is renamed to `<name>.converted` afterwards, so backend
+/// detection picks up the new one. Refuses to replace an existing target.
+pub fn convert(data_dir: &Path, to: StorageKind) -> Result<UserHistory> {
+    let from = match to {
+        StorageKind::Json => StorageKind::Sqlite,
+        StorageKind::Sqlite => StorageKind::Json,
+    };
+    let mut source = open(from, data_dir);
+    let mut target = open(to, data_dir);
+
+    if !source.path().exists() {
+        return Err(KanaError::InvalidInput(format!(
+            "Nothing to convert: {} does not exist",
+            source.path().display()
+        )));
+    }
+    if target.path().exists() {
+        return Err(KanaError::InvalidInput(format!(
+            "{} already exists; move it away first",
+            target.path().display()
+        )));
+    }
+
+    let history = source.load()?;
+    target.save(&history)?;
+
+    let mut converted = source.path().as_os_str().to_os_string();
+    converted.push(".converted");
+    fs::rename(source.path(), &converted)?;
+
+    info!(
+        from = %source.path().display(),
+        to = %target.path().display(),
+        "Converted history"
+    );
+    Ok(history)
+}
+
+/// Fails if `path` was written with a schema newer than this build supports.
+pub fn check_not_newer(path: &Path, found: u32, supported: u32) -> Result<()> {
+    if found > supported {
+        return Err(KanaError::NewerSchema {
+            path: path.display().to_string(),
+            found,
+            supported,
+        });
+    }
+    Ok(())
+}
+
+/// Recomputes derived values after loading, warning if the stored EMAs disagree.
+pub fn finish_load(history: &mut UserHistory) {
+    for (kana, stats) in history.character_stats.iter_mut().chain(history.reverse_stats.iter_mut()) {
+        let stored_ema_response = stats.exp_avg_response;
+        let stored_ema_accuracy = stats.exp_avg_accuracy;
+
+        stats.recalculate_ema();
+        if stats.due.is_none() && !stats.test_history.is_empty() {
+            stats.rebuild_schedule();
+        }
+
+        if (stats.exp_avg_response - stored_ema_response).abs() > 1e-10
+            || (stats.exp_avg_accuracy - stored_ema_accuracy).abs() > 1e-10
+        {
+            warn!(
+                kana = kana,
+                stored_response = stored_ema_response,
+                stored_accuracy = stored_ema_accuracy,
+                recalculated_response = stats.exp_avg_response,
+                recalculated_accuracy = stats.exp_avg_accuracy,
+                "EMA mismatch detected"
+            );
+        }
+    }
+}
+
+/// Reads and writes one JSON history file.
+///
+/// Writes go to a temporary file that is renamed over the original, so a crash
+/// mid-write never leaves a truncated history behind. The first write of each
+/// run also rotates the previous file into `<name>.1` … `<name>.3`.
+pub struct JsonStore {
+    path: PathBuf,
+    backups_rotated: bool,
+    // Schema version of the file on disk, once it has been read or written
+    on_disk_version: Option<u32>,
+}
+
+impl JsonStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            backups_rotated: false,
+            on_disk_version: None,
+        }
+    }
+
+    fn sibling(&self, suffix: &str) -> PathBuf {
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(format!(".{}", suffix));
+        self.path.with_file_name(name)
+    }
+
+    // Shifts <name>.1 → <name>.2 → … and copies the current file to <name>.1
+    fn rotate_backups(&self) -> Result<()> {
+        if !self.path.exists() {
+            return Ok(());
+        }
+
+        for n in (1..BACKUP_COUNT).rev() {
+            let from = self.sibling(&n.to_string());
+            if from.exists() {
+                fs::rename(&from, self.sibling(&(n + 1).to_string()))?;
+            }
+        }
+        fs::copy(&self.path, self.sibling("1"))?;
+
+        info!(path = %self.path.display(), backups = BACKUP_COUNT, "Rotated history backups");
+        Ok(())
+    }
+}
+
+impl Storage for JsonStore {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Older schemas are migrated; files from a newer version are refused.
+    fn load(&mut self) -> Result<UserHistory> {
+        if !self.path.exists() {
+            return Ok(UserHistory::default());
+        }
+
+        let mut document: Value = serde_json::from_reader(BufReader::new(File::open(&self.path)?))?;
+        let version = migrations::schema_version(&document);
+        check_not_newer(&self.path, version, CURRENT_SCHEMA_VERSION)?;
+        self.on_disk_version = Some(version);
+        let from = migrations::migrate(&mut document)?;
+        if from < CURRENT_SCHEMA_VERSION {
+            info!(
+                path = %self.path.display(),
+                from = from,
+                to = CURRENT_SCHEMA_VERSION,
+                "History upgraded, the original is kept as a backup on the first save"
+            );
+        }
+        let mut history: UserHistory = serde_json::from_value(document)?;
+        finish_load(&mut history);
+
+        Ok(history)
+    }
+
+    /// Refuses to write over a file written by a newer version. The version
+    /// is remembered from loading, so only a store that was never loaded reads
+    /// the file first.
+    fn save(&mut self, history: &UserHistory) -> Result<()> {
+        if self.on_disk_version.is_none() && self.path.exists() {
+            let on_disk: VersionProbe = serde_json::from_reader(BufReader::new(File::open(&self.path)?))?;
+            self.on_disk_version = Some(on_disk.schema_version);
+        }
+        if let Some(version) = self.on_disk_version {
+            check_not_newer(&self.path, version, CURRENT_SCHEMA_VERSION)?;
+        }
+
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+
+        if !self.backups_rotated {
+            self.rotate_backups()?;
+            self.backups_rotated = true;
+        }
+
+        let tmp_path = self.sibling("tmp");
+        {
+            let mut writer = BufWriter::new(File::create(&tmp_path)?);
+            serde_json::to_writer_pretty(&mut writer, history)?;
+            writer.flush()?;
+            writer.get_ref().sync_all()?;
+        }
+        fs::rename(&tmp_path, &self.path)?;
+        self.on_disk_version = Some(CURRENT_SCHEMA_VERSION);
+
+        Ok(())
+    }
+}
+
+// Reads only the version of a stored history, skipping over everything else
+#[derive(Deserialize)]
+struct VersionProbe {
+    #[serde(default)]
+    schema_version: u32,
+}
+
+/// Saves the history every few answers or seconds, whichever comes first.
+pub struct Autosaver {
+    store: Box<dyn Storage>,
+    every_answers: u32,
+    interval: Duration,
+    last_save: Instant,
+}
+
+impl Autosaver {
+    pub const DEFAULT_EVERY_ANSWERS: u32 = 10;
+    pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(60);
+
+    pub fn new(store: Box<dyn Storage>, every_answers: u32, interval: Duration) -> Self {
+        Self {
+            store,
+            every_answers,
+            interval,
+            last_save: Instant::now(),
+        }
+    }
+
+    /// Saves if enough answers or time have passed since the last save.
+    /// Returns whether anything was written.
+    pub fn tick(&mut self, history: &UserHistory, unsaved_answers: u32) -> Result<bool> {
+        if unsaved_answers == 0 {
+            return Ok(false);
+        }
+        if unsaved_answers < self.every_answers && self.last_save.elapsed() < self.interval {
+            return Ok(false);
+        }
+        self.save(history)?;
+        Ok(true)
+    }
+
+    pub fn save(&mut self, history: &UserHistory) -> Result<()> {
+        self.store.save(history)?;
+        self.last_save = Instant::now();
+        info!(path = %self.store.path().display(), "History saved");
+        Ok(())
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+
+use crate::error::{KanaError, Result};
+use crate::kana::kana_set;
+use crate::types::*;
+use chrono::{DateTime, Utc};
+
+const HEADER: &str = "kana,kana_type,set,exercise,input,start_time,duration_ms,success,hints,first_key_ms,backspaces,key_intervals_ms,keystrokes,idle";
+const SETS: [PracticeMode; 3] = [PracticeMode::Main, PracticeMode::Dakuten, PracticeMode::Combination];
+// The two stats maps of `UserHistory`, by the exercise that writes them
+const STATS_SETS: [ExerciseMode; 2] = [ExerciseMode::Romaji, ExerciseMode::Reverse];
+
+fn classify(kana: &str) -> Option<(KanaType, PracticeMode)> {
+    [KanaType::Hiragana, KanaType::Katakana]
+        .into_iter()
+        .flat_map(|kana_type| SETS.map(|set| (kana_type, set)))
+        .find(|&(kana_type, set)| kana_set(kana_type, set).iter().any(|entry| entry.kana == kana))
+}
+
+// Quotes a field if it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Writes every attempt as one CSV row, oldest first within each kana.
+/// Returns the number of rows written.
+pub fn export_csv(history: &UserHistory, mut out: impl Write) -> Result<usize> {
+    writeln!(out, "{}", HEADER)?;
+    let mut rows = 0;
+
+    for exercise in STATS_SETS {
+        let stats = history.stats_for(exercise);
+        let mut kana: Vec<&String> = stats.keys().collect();
+        kana.sort();
+
+        for kana in kana {
+            let (kana_type, set) = match classify(kana) {
+                Some((kana_type, set)) => (kana_type.to_string(), set.to_string()),
+                None => (String::new(), String::new()),
+            };
+            for attempt in &stats[kana].test_history {
+                writeln!(
+                    out,
+                    "{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                    csv_field(kana),
+                    kana_type,
+                    set,
+                    exercise,
+                    csv_field(&attempt.input),
+                    attempt.start_time.to_rfc3339(),
+                    attempt.duration_ms,
+                    attempt.success,
+                    attempt.hints,
+                    attempt.first_key_ms().map(|ms| ms.to_string()).unwrap_or_default(),
+                    attempt.backspaces(),
+                    attempt.key_intervals_ms().iter().map(|ms| ms.to_string()).collect::<Vec<_>>().join(" "),
+                    Keystroke::encode(&attempt.keystrokes),
+                    attempt.idle,
+                )?;
+                rows += 1;
+            }
+        }
+    }
+
+    Ok(rows)
+}
+
+/// Reads attempts written by `export_csv` and merges them into `history`.
+///
+/// Attempts whose start time is already recorded for that kana are skipped,
+/// and the statistics of every touched kana are rebuilt from its attempts.
+/// Returns the number of rows read and the number of attempts added.
+pub fn import_csv(history: &mut UserHistory, input: impl BufRead) -> Result<(usize, usize)> {
+    let mut imported: HashMap<(ExerciseMode, String), CharacterStats> = HashMap::new();
+    let mut rows = 0;
+    let mut columns: HashMap<String, usize> = HashMap::new();
+
+    // Quote-aware, so inputs with line breaks in them stay within their field
+    let mut reader = csv::ReaderBuilder::new().has_headers(false).flexible(true).from_reader(input);
+    for record in reader.records() {
+        let fields = record?;
+        let line_number = fields.position().map_or(0, |position| position.line());
+        if fields.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+        if columns.is_empty() {
+            columns = fields.iter().enumerate().map(|(idx, name)| (name.to_string(), idx)).collect();
+            for required in ["kana", "exercise", "input", "start_time", "duration_ms", "success"] {
+                if !columns.contains_key(required) {
+                    return Err(KanaError::InvalidInput(format!("CSV header is missing the '{}' column", required)));
+                }
+            }
+            continue;
+        }
+
+        let invalid = |what: &str| {
+            KanaError::InvalidInput(format!("Line {}: invalid {}", line_number, what))
+        };
+        let field = |name: &str| {
+            columns.get(name).and_then(|&idx| fields.get(idx)).unwrap_or_default()
+        };
+
+        let exercise = match field("exercise") {
+            "reverse" => ExerciseMode::Reverse,
+            "romaji" | "choice" | "words" | "" => ExerciseMode::Romaji,
+            _ => return Err(invalid("exercise")),
+        };
+        let start_time: DateTime<Utc> = DateTime::parse_from_rfc3339(field("start_time"))
+            .map_err(|_| invalid("start_time"))?
+            .with_timezone(&Utc);
+        let duration_ms: f64 = field("duration_ms").parse().map_err(|_| invalid("duration_ms"))?;
+        let success: bool = field("success").parse().map_err(|_| invalid("success"))?;
+        // Files exported before hints existed have no such column
+        let hints: u32 = match field("hints") {
+            "" => 0,
+            value => value.parse().map_err(|_| invalid("hints"))?,
+        };
+        // first_key_ms, backspaces and key_intervals_ms are derived from the keystrokes
+        let keystrokes = Keystroke::decode(field("keystrokes")).ok_or_else(|| invalid("keystrokes"))?;
+        let idle = match field("idle") {
+            "" => false,
+            value => value.parse().map_err(|_| invalid("idle"))?,
+        };
+        let input = field("input").to_string();
+        let kana = field("kana");
+        if kana.is_empty() {
+            return Err(invalid("kana"));
+        }
+
+        let stats = imported
+            .entry((exercise, kana.to_string()))
+            .or_insert_with(CharacterStats::new);
+        if !success {
+            stats.mistakes.push(MistakeEntry {
+                input: input.clone(),
+                timestamp: start_time + chrono::Duration::milliseconds(duration_ms as i64),
+            });
+        }
+        stats.test_history.push(TestEntry {
+            input,
+            start_time,
+            duration_ms,
+            success,
+            hints,
+            keystrokes,
+            idle,
+        });
+        rows += 1;
+    }
+
+    let mut added = 0;
+    for ((exercise, kana), stats) in imported {
+        let target = history
+            .stats_for_mut(exercise)
+            .entry(kana)
+            .or_insert_with(CharacterStats::new);
+        let before = target.test_history.len();
+        target.merge(&stats);
+        added += target.test_history.len() - before;
+    }
+
+    Ok((rows, added))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_round_trip_keeps_inputs_with_line_breaks() {
+        let start = Utc::now();
+        let mut history = UserHistory::default();
+        let stats = history.character_stats.entry("し".to_string()).or_insert_with(CharacterStats::new);
+        for (idx, input) in ["shi", "s\"i,\nx", "si"].into_iter().enumerate() {
+            stats.record_attempt(TestEntry {
+                input: input.to_string(),
+                start_time: start + chrono::Duration::seconds(idx as i64),
+                duration_ms: 812.5,
+                success: input == "shi",
+                hints: 1,
+                keystrokes: vec![Keystroke { at_ms: 300.0, backspace: false }, Keystroke { at_ms: 450.5, backspace: true }],
+                idle: false,
+            });
+        }
+
+        let mut csv = Vec::new();
+        assert_eq!(export_csv(&history, &mut csv).unwrap(), 3);
+        assert!(String::from_utf8_lossy(&csv).contains(",1,150.5,300 b450.5,"));
+
+        let mut imported = UserHistory::default();
+        assert_eq!(import_csv(&mut imported, csv.as_slice()).unwrap(), (3, 3));
+        assert_eq!(
+            serde_json::to_value(&imported.character_stats["し"].test_history).unwrap(),
+            serde_json::to_value(&history.character_stats["し"].test_history).unwrap(),
+        );
+    }
+}
@@ -1,7 +1,11 @@
-use crate::kana::*;
+use crate::app::{DEFAULT_IDLE_AFTER, DEFAULT_REVEAL_AFTER};
+use crate::words::Word;
+use crate::migrations::CURRENT_SCHEMA_VERSION;
+use crate::mnemonics::Mnemonics;
+use crate::profiles::DEFAULT_PROFILE;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use tracing::debug;
 
@@ -17,6 +21,78 @@ pub struct TestEntry {
     pub start_time: DateTime<Utc>,
     pub duration_ms: f64,
     pub success: bool,
+    /// Hint steps taken before answering.
+    #[serde(default)]
+    pub hints: u32,
+    /// Keys pressed before submitting, oldest first. Empty for attempts
+    /// recorded before keystrokes were timed, and for word practice.
+    #[serde(default)]
+    pub keystrokes: Vec<Keystroke>,
+    /// The answer took longer than the idle threshold, so the learner likely
+    /// looked away. Such attempts count for accuracy but not for response times.
+    #[serde(default)]
+    pub idle: bool,
+}
+
+/// One key pressed while answering, in milliseconds since the prompt appeared.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Keystroke {
+    pub at_ms: f64,
+    #[serde(default)]
+    pub backspace: bool,
+}
+
+impl Keystroke {
+    /// Compact text form used by CSV exports and the SQLite store: offsets
+    /// separated by spaces, backspaces marked with `b`, e.g. `412 530 b801 950`.
+    pub fn encode(keys: &[Keystroke]) -> String {
+        keys.iter()
+            .map(|key| format!("{}{}", if key.backspace { "b" } else { "" }, key.at_ms))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    pub fn decode(text: &str) -> Option<Vec<Keystroke>> {
+        text.split_whitespace()
+            .map(|token| {
+                let (backspace, at_ms) = match token.strip_prefix('b') {
+                    Some(rest) => (true, rest),
+                    None => (false, token),
+                };
+                Some(Keystroke { at_ms: at_ms.parse().ok()?, backspace })
+            })
+            .collect()
+    }
+}
+
+impl TestEntry {
+    /// Time until the first key: how long it took to recognise the prompt,
+    /// without the typing that followed.
+    pub fn first_key_ms(&self) -> Option<f64> {
+        self.keystrokes.first().map(|key| key.at_ms)
+    }
+
+    /// Time between consecutive keys.
+    pub fn key_intervals_ms(&self) -> Vec<f64> {
+        self.keystrokes.windows(2).map(|pair| pair[1].at_ms - pair[0].at_ms).collect()
+    }
+
+    pub fn backspaces(&self) -> usize {
+        self.keystrokes.iter().filter(|key| key.backspace).count()
+    }
+
+    // Share of a correct answer's credit lost per hint step
+    const HINT_PENALTY: f64 = 0.5;
+
+    /// How much the attempt counts towards the accuracy EMA: a correct answer
+    /// loses half its credit per hint, so two hints leave nothing.
+    pub fn accuracy_score(&self) -> f64 {
+        if self.success {
+            (1.0 - Self::HINT_PENALTY * self.hints as f64).max(0.0)
+        } else {
+            0.0
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,12 +104,32 @@ pub struct CharacterStats {
     pub last_appearance: DateTime<Utc>,
     pub exp_avg_response: f64,
     pub exp_avg_accuracy: f64,
+    /// EMA of the time to the first key, over the attempts that have keystrokes.
+    #[serde(default)]
+    pub exp_avg_first_key: Option<f64>,
     pub mistakes: Vec<MistakeEntry>,
     pub test_history: Vec<TestEntry>,
+    // SM-2 spaced repetition state
+    #[serde(default = "default_ease")]
+    pub ease: f64,
+    #[serde(default)]
+    pub interval_days: f64,
+    #[serde(default)]
+    pub repetitions: u32,
+    #[serde(default)]
+    pub due: Option<DateTime<Utc>>,
+}
+
+fn default_ease() -> f64 {
+    CharacterStats::INITIAL_EASE
 }
 
 impl CharacterStats {
     const ALPHA: f64 = 0.2;
+    const INITIAL_EASE: f64 = 2.5;
+    const MIN_EASE: f64 = 1.3;
+    // Typical time to the first key, the recognition counterpart of the 1200ms response median
+    const FIRST_KEY_MEDIAN_MS: f64 = 700.0;
 
     pub fn new() -> Self {
         Self {
@@ -43,26 +139,17 @@ impl CharacterStats {
             total_response_time: 0.0,
             exp_avg_response: 0.0,
             exp_avg_accuracy: 0.0,
+            exp_avg_first_key: None,
             last_appearance: Utc::now(),
             mistakes: Vec::new(),
             test_history: Vec::new(),
+            ease: Self::INITIAL_EASE,
+            interval_days: 0.0,
+            repetitions: 0,
+            due: None,
         }
     }
 
-    pub fn success_rate(&self) -> f64 {
-        if self.appearances == 0 {
-            return 0.0;
-        }
-        self.successes as f64 / self.appearances as f64
-    }
-
-    pub fn avg_response_time(&self) -> f64 {
-        if self.appearances == 0 {
-            return 0.0;
-        }
-        self.total_response_time / self.appearances as f64
-    }
-
     pub fn calculate_weight(&self, now: DateTime<Utc>) -> f64 {
         // Base weight for characters never seen
         if self.appearances == 0 {
@@ -84,8 +171,12 @@ impl CharacterStats {
         let recency_component = 1.0 / (1.0 + (-0.002 * (seconds_since - 1800.0)).exp());
 
         // 3. Response time component (0-1, higher means needs practice)
-        // Using 1200ms as the median response time
-        let response_component = 1.0 / (1.0 + (-0.005 * (self.exp_avg_response - 1200.0)).exp());
+        // Time to the first key measures recognition alone, so prefer it once known.
+        // Otherwise use the full response time, with 1200ms as its median.
+        let response_component = match self.exp_avg_first_key {
+            Some(first_key) => 1.0 / (1.0 + (-0.005 * (first_key - Self::FIRST_KEY_MEDIAN_MS)).exp()),
+            None => 1.0 / (1.0 + (-0.005 * (self.exp_avg_response - 1200.0)).exp()),
+        };
 
         // Calculate final weight as average of components plus base
         let components_avg = (error_component + recency_component + response_component) / 3.0;
@@ -98,6 +189,7 @@ impl CharacterStats {
             seconds_since = seconds_since,
             exp_avg_accuracy = self.exp_avg_accuracy,
             exp_avg_response = self.exp_avg_response,
+            exp_avg_first_key = ?self.exp_avg_first_key,
             components_avg = components_avg,
             final_weight = weight,
             appearances = self.appearances,
@@ -107,55 +199,112 @@ impl CharacterStats {
         weight
     }
 
-    pub fn get_weight_components(&self, now: DateTime<Utc>) -> (f64, f64, f64) {
-        if self.appearances == 0 {
-            return (5.0, 0.0, 0.0);
-        }
-
-        let hours_since = (now - self.last_appearance).num_hours().min(24) as f64;
-        let recency_factor = hours_since / 24.0;
-        let error_rate = 1.0 - self.exp_avg_accuracy;
-        let response_factor = (self.exp_avg_response / 5000.0).min(1.0);
-
-        (
-            error_rate * 3.0,
-            response_factor * 0.5,
-            recency_factor * 1.0,
-        )
-    }
-
-    pub fn record_attempt(&mut self, input: &str, success: bool, response_time: f64) {
+    /// Adds a finished attempt. It ended at `start_time + duration_ms`.
+    pub fn record_attempt(&mut self, entry: TestEntry) {
+        let now = entry.start_time + chrono::Duration::milliseconds(entry.duration_ms as i64);
         self.appearances += 1;
 
-        if success {
+        if entry.success {
             self.successes += 1;
         } else {
             self.failures += 1;
             self.mistakes.push(MistakeEntry {
-                input: input.to_string(),
-                timestamp: Utc::now(),
+                input: entry.input.clone(),
+                timestamp: now,
             });
         }
 
-        self.test_history.push(TestEntry {
-            input: input.to_string(),
-            start_time: Utc::now() - chrono::Duration::milliseconds(response_time as i64),
-            duration_ms: response_time,
-            success,
-        });
-
+        let score = entry.accuracy_score();
         if self.appearances == 1 {
-            self.exp_avg_response = response_time;
-            self.exp_avg_accuracy = if success { 1.0 } else { 0.0 };
+            self.exp_avg_accuracy = score;
         } else {
-            self.exp_avg_response =
-                Self::ALPHA * response_time + (1.0 - Self::ALPHA) * self.exp_avg_response;
-            self.exp_avg_accuracy = Self::ALPHA * (if success { 1.0 } else { 0.0 })
-                + (1.0 - Self::ALPHA) * self.exp_avg_accuracy;
+            self.exp_avg_accuracy = Self::ALPHA * score + (1.0 - Self::ALPHA) * self.exp_avg_accuracy;
         }
 
-        self.total_response_time += response_time;
-        self.last_appearance = Utc::now();
+        // Idle attempts say nothing about speed
+        if !entry.idle {
+            let first_timed = self.test_history.iter().all(|earlier| earlier.idle);
+            self.exp_avg_response = if first_timed {
+                entry.duration_ms
+            } else {
+                Self::ALPHA * entry.duration_ms + (1.0 - Self::ALPHA) * self.exp_avg_response
+            };
+            self.exp_avg_first_key = Self::next_first_key_ema(self.exp_avg_first_key, &entry);
+            self.total_response_time += entry.duration_ms;
+        }
+
+        self.last_appearance = now;
+        self.update_schedule(entry.success, entry.duration_ms, entry.hints, now);
+        self.test_history.push(entry);
+    }
+
+    // Attempts without keystrokes leave the first-key EMA as it was
+    fn next_first_key_ema(avg: Option<f64>, entry: &TestEntry) -> Option<f64> {
+        match (entry.first_key_ms(), avg) {
+            (Some(first_key), Some(avg)) => Some(Self::ALPHA * first_key + (1.0 - Self::ALPHA) * avg),
+            (first_key, avg) => first_key.or(avg),
+        }
+    }
+
+    /// Whether the kana is due for review. Kana that were never reviewed are always due.
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.due.is_none_or(|due| due <= now)
+    }
+
+    // Recall quality on the SM-2 0-5 scale, graded by correctness and response time.
+    // A correct answer that needed hints only counts as recalled after seeing it.
+    fn review_quality(success: bool, response_time: f64, hints: u32) -> f64 {
+        match (success, response_time) {
+            (false, _) => 1.0,
+            (true, _) if hints > 0 => 2.0,
+            (true, t) if t < 1500.0 => 5.0,
+            (true, t) if t < 3000.0 => 4.0,
+            (true, _) => 3.0,
+        }
+    }
+
+    /// Applies one SM-2 review.
+    ///
+    /// A correct answer only moves the schedule forward when the kana was due, so
+    /// drilling it several times in one session doesn't inflate the interval.
+    /// A wrong answer, or a correct one that needed hints, resets it and makes
+    /// the kana due again right away.
+    fn update_schedule(&mut self, success: bool, response_time: f64, hints: u32, now: DateTime<Utc>) {
+        if success && !self.is_due(now) {
+            return;
+        }
+
+        let quality = Self::review_quality(success, response_time, hints);
+        if quality >= 3.0 {
+            self.interval_days = match self.repetitions {
+                0 => 1.0,
+                1 => 6.0,
+                _ => (self.interval_days * self.ease).round(),
+            };
+            self.repetitions += 1;
+        } else {
+            self.repetitions = 0;
+            self.interval_days = 0.0;
+        }
+
+        self.ease = (self.ease + 0.1 - (5.0 - quality) * (0.08 + (5.0 - quality) * 0.02))
+            .max(Self::MIN_EASE);
+        self.due = Some(now + chrono::Duration::seconds((self.interval_days * 86400.0) as i64));
+    }
+
+    /// Rebuilds the SM-2 state by replaying `test_history`, for files written before it existed.
+    pub fn rebuild_schedule(&mut self) {
+        self.ease = Self::INITIAL_EASE;
+        self.interval_days = 0.0;
+        self.repetitions = 0;
+        self.due = None;
+
+        let history = std::mem::take(&mut self.test_history);
+        for entry in &history {
+            let answered_at = entry.start_time + chrono::Duration::milliseconds(entry.duration_ms as i64);
+            self.update_schedule(entry.success, entry.duration_ms, entry.hints, answered_at);
+        }
+        self.test_history = history;
     }
 
     pub fn get_ema_accuracy(&self) -> f64 {
@@ -165,66 +314,191 @@ impl CharacterStats {
     pub fn recalculate_ema(&mut self) {
         self.exp_avg_response = 0.0;
         self.exp_avg_accuracy = 0.0;
+        self.exp_avg_first_key = None;
 
         for (i, entry) in self.test_history.iter().enumerate() {
             if i == 0 {
-                self.exp_avg_response = entry.duration_ms;
-                self.exp_avg_accuracy = if entry.success { 1.0 } else { 0.0 };
+                self.exp_avg_accuracy = entry.accuracy_score();
             } else {
-                self.exp_avg_response =
-                    Self::ALPHA * entry.duration_ms + (1.0 - Self::ALPHA) * self.exp_avg_response;
-                self.exp_avg_accuracy = Self::ALPHA * (if entry.success { 1.0 } else { 0.0 })
+                self.exp_avg_accuracy = Self::ALPHA * entry.accuracy_score()
                     + (1.0 - Self::ALPHA) * self.exp_avg_accuracy;
             }
         }
-    }
 
-    pub fn get_recent_avg_response_time(&self, n: usize) -> f64 {
-        let recent_tests = self.test_history.iter().rev().take(n);
-        let (sum, count) = recent_tests.fold((0.0, 0), |(sum, count), entry| {
-            (sum + entry.duration_ms, count + 1)
-        });
-        if count == 0 {
-            0.0
-        } else {
-            sum / count as f64
+        let mut timed = self.test_history.iter().filter(|entry| !entry.idle);
+        if let Some(first) = timed.next() {
+            self.exp_avg_response = first.duration_ms;
+            self.exp_avg_first_key = first.first_key_ms();
         }
+        for entry in timed {
+            self.exp_avg_response =
+                Self::ALPHA * entry.duration_ms + (1.0 - Self::ALPHA) * self.exp_avg_response;
+            self.exp_avg_first_key = Self::next_first_key_ema(self.exp_avg_first_key, entry);
+        }
+    }
+
+    /// Adds the attempts and mistakes from `other`, skipping any already present
+    /// with the same start time, and recomputes everything derived from them.
+    pub fn merge(&mut self, other: &CharacterStats) {
+        let known: HashSet<DateTime<Utc>> = self.test_history.iter().map(|entry| entry.start_time).collect();
+        self.test_history.extend(
+            other.test_history.iter().filter(|entry| !known.contains(&entry.start_time)).cloned(),
+        );
+        self.test_history.sort_by_key(|entry| entry.start_time);
+
+        let known: HashSet<DateTime<Utc>> = self.mistakes.iter().map(|entry| entry.timestamp).collect();
+        self.mistakes.extend(
+            other.mistakes.iter().filter(|entry| !known.contains(&entry.timestamp)).cloned(),
+        );
+        self.mistakes.sort_by_key(|entry| entry.timestamp);
+
+        self.recompute_from_history();
     }
 
-    pub fn get_recent_success_rate(&self, n: usize) -> f64 {
-        let recent_tests: Vec<_> = self.test_history.iter().rev().take(n).collect();
-        if recent_tests.is_empty() {
-            return 0.0;
+    /// Recomputes the counters, EMAs and review schedule from `test_history`.
+    pub fn recompute_from_history(&mut self) {
+        self.appearances = self.test_history.len() as u32;
+        self.successes = self.test_history.iter().filter(|entry| entry.success).count() as u32;
+        self.failures = self.appearances - self.successes;
+        self.total_response_time = self.test_history
+            .iter()
+            .filter(|entry| !entry.idle)
+            .map(|entry| entry.duration_ms)
+            .sum();
+        if let Some(last) = self.test_history.last() {
+            self.last_appearance = last.start_time + chrono::Duration::milliseconds(last.duration_ms as i64);
         }
-        let successes = recent_tests.iter().filter(|entry| entry.success).count();
-        successes as f64 / recent_tests.len() as f64
+        self.recalculate_ema();
+        self.rebuild_schedule();
     }
+
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserHistory {
+    #[serde(default)]
+    pub schema_version: u32,
     pub character_stats: HashMap<String, CharacterStats>,
+    // Romaji → kana attempts, kept apart so recall and recognition are measured independently
+    #[serde(default)]
+    pub reverse_stats: HashMap<String, CharacterStats>,
     pub last_session: DateTime<Utc>,
+    /// Active seconds over all sessions.
     pub total_practice_time: f64,
+    /// Oldest first.
+    #[serde(default)]
+    pub sessions: Vec<SessionRecord>,
+}
+
+/// One sitting in the TUI, from its first answer until quitting or switching
+/// to another kana type or practice set.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Time spent answering. Pauses and feedback don't count, and idle
+    /// answers only up to the idle threshold.
+    pub active_seconds: f64,
+    pub attempts: u32,
+    pub successes: u32,
+    pub kana_type: KanaType,
+    pub practice_mode: PracticeMode,
+    pub exercise: ExerciseMode,
+}
+
+impl SessionRecord {
+    pub fn accuracy(&self) -> f64 {
+        if self.attempts == 0 {
+            0.0
+        } else {
+            self.successes as f64 / self.attempts as f64
+        }
+    }
+
+    /// Whether both sessions practised the same thing, so they can be compared.
+    pub fn same_practice(&self, other: &SessionRecord) -> bool {
+        self.kana_type == other.kana_type
+            && self.practice_mode == other.practice_mode
+            && self.exercise == other.exercise
+    }
 }
 
 impl Default for UserHistory {
     fn default() -> Self {
         Self {
+            schema_version: CURRENT_SCHEMA_VERSION,
             character_stats: HashMap::new(),
+            reverse_stats: HashMap::new(),
             last_session: Utc::now(),
             total_practice_time: 0.0,
+            sessions: Vec::new(),
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+impl UserHistory {
+    /// Folds another history into this one, e.g. from a second machine or profile.
+    /// Attempts present in both are only counted once, so merging the same
+    /// history twice changes nothing.
+    pub fn merge(&mut self, other: &UserHistory) {
+        for (mine, theirs) in [
+            (&mut self.character_stats, &other.character_stats),
+            (&mut self.reverse_stats, &other.reverse_stats),
+        ] {
+            for (kana, stats) in theirs {
+                mine.entry(kana.clone()).or_insert_with(CharacterStats::new).merge(stats);
+            }
+        }
+        let known: HashSet<DateTime<Utc>> = self.sessions.iter().map(|session| session.start).collect();
+        self.sessions.extend(
+            other.sessions.iter().filter(|session| !known.contains(&session.start)).cloned(),
+        );
+        self.sessions.sort_by_key(|session| session.start);
+
+        self.last_session = self.last_session.max(other.last_session);
+        // Practice time was never recorded before sessions, so the sessions hold all of it
+        self.total_practice_time = self.sessions.iter().map(|session| session.active_seconds).sum();
+    }
+
+    /// Files a finished session.
+    pub fn add_session(&mut self, session: SessionRecord) {
+        self.last_session = session.end;
+        self.total_practice_time += session.active_seconds;
+        self.sessions.push(session);
+    }
+
+    pub fn attempt_count(&self) -> usize {
+        self.character_stats
+            .values()
+            .chain(self.reverse_stats.values())
+            .map(|stats| stats.test_history.len())
+            .sum()
+    }
+
+    pub fn stats_for(&self, exercise: ExerciseMode) -> &HashMap<String, CharacterStats> {
+        match exercise {
+            ExerciseMode::Romaji | ExerciseMode::Choice | ExerciseMode::Words => &self.character_stats,
+            ExerciseMode::Reverse => &self.reverse_stats,
+        }
+    }
+
+    pub fn stats_for_mut(&mut self, exercise: ExerciseMode) -> &mut HashMap<String, CharacterStats> {
+        match exercise {
+            ExerciseMode::Romaji | ExerciseMode::Choice | ExerciseMode::Words => &mut self.character_stats,
+            ExerciseMode::Reverse => &mut self.reverse_stats,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum KanaType {
     Hiragana,
     Katakana,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum PracticeMode {
     Main,
     Dakuten,
@@ -232,6 +506,61 @@ pub enum PracticeMode {
     All,
 }
 
+/// Which romanization systems are accepted as answers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Romanization {
+    #[default]
+    Hepburn,
+    Kunrei,
+    Nihon,
+    Any,
+}
+
+/// How the next kana is chosen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SchedulerKind {
+    #[default]
+    Weighted, // Random, weighted by error rate, recency and response time
+    Sm2,      // Most overdue kana first, using SM-2 intervals
+    Uniform,  // Every kana equally likely
+    Deck,     // Shuffled deck, each kana once per round
+    Weakest,  // Lowest recent accuracy first
+}
+
+/// What the learner is asked to produce for each prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExerciseMode {
+    #[default]
+    Romaji,  // Kana shown, type the romaji
+    Reverse, // Romaji shown, type or pick the kana
+    Choice,  // Kana shown, pick the romaji from numbered options
+    Words,   // Whole word shown, type its romaji
+}
+
+/// What a day of practice takes to count towards the streak.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DailyGoal {
+    Correct(u32), // Correct answers
+    Minutes(u32), // Minutes of active practice
+}
+
+impl Default for DailyGoal {
+    fn default() -> Self {
+        DailyGoal::Correct(20)
+    }
+}
+
+/// Where the history is kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageKind {
+    Json,   // One pretty-printed document, rewritten on every save
+    Sqlite, // One row per attempt, new attempts appended on save
+}
+
 impl fmt::Display for KanaType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -252,17 +581,98 @@ impl fmt::Display for PracticeMode {
     }
 }
 
+impl fmt::Display for Romanization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Romanization::Hepburn => write!(f, "hepburn"),
+            Romanization::Kunrei => write!(f, "kunrei"),
+            Romanization::Nihon => write!(f, "nihon"),
+            Romanization::Any => write!(f, "any"),
+        }
+    }
+}
+
+impl fmt::Display for SchedulerKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchedulerKind::Weighted => write!(f, "weighted"),
+            SchedulerKind::Sm2 => write!(f, "sm2"),
+            SchedulerKind::Uniform => write!(f, "uniform"),
+            SchedulerKind::Deck => write!(f, "deck"),
+            SchedulerKind::Weakest => write!(f, "weakest"),
+        }
+    }
+}
+
+impl fmt::Display for DailyGoal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DailyGoal::Correct(count) => write!(f, "{} correct answers", count),
+            DailyGoal::Minutes(minutes) => write!(f, "{} minutes", minutes),
+        }
+    }
+}
+
+impl fmt::Display for StorageKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageKind::Json => write!(f, "json"),
+            StorageKind::Sqlite => write!(f, "sqlite"),
+        }
+    }
+}
+
+impl fmt::Display for ExerciseMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExerciseMode::Romaji => write!(f, "romaji"),
+            ExerciseMode::Reverse => write!(f, "reverse"),
+            ExerciseMode::Choice => write!(f, "choice"),
+            ExerciseMode::Words => write!(f, "words"),
+        }
+    }
+}
+
+// Cycling order for the settings hotkeys on the start and pause screens
 impl KanaType {
-    pub fn get_practice_set_size(self, mode: PracticeMode) -> usize {
-        match (self, mode) {
-            (KanaType::Hiragana, PracticeMode::Main) => MAIN_HIRAGANA.len(),
-            (KanaType::Hiragana, PracticeMode::Dakuten) => DAKUTEN_HIRAGANA.len(),
-            (KanaType::Hiragana, PracticeMode::Combination) => COMBINATION_HIRAGANA.len(),
-            (KanaType::Hiragana, PracticeMode::All) => ALL_HIRAGANA.len(),
-            (KanaType::Katakana, PracticeMode::Main) => MAIN_KATAKANA.len(),
-            (KanaType::Katakana, PracticeMode::Dakuten) => DAKUTEN_KATAKANA.len(),
-            (KanaType::Katakana, PracticeMode::Combination) => COMBINATION_KATAKANA.len(),
-            (KanaType::Katakana, PracticeMode::All) => ALL_KATAKANA.len(),
+    pub fn next(self) -> Self {
+        match self {
+            KanaType::Hiragana => KanaType::Katakana,
+            KanaType::Katakana => KanaType::Hiragana,
+        }
+    }
+}
+
+impl PracticeMode {
+    pub fn next(self) -> Self {
+        match self {
+            PracticeMode::Main => PracticeMode::Dakuten,
+            PracticeMode::Dakuten => PracticeMode::Combination,
+            PracticeMode::Combination => PracticeMode::All,
+            PracticeMode::All => PracticeMode::Main,
+        }
+    }
+}
+
+impl Romanization {
+    pub fn next(self) -> Self {
+        match self {
+            Romanization::Hepburn => Romanization::Kunrei,
+            Romanization::Kunrei => Romanization::Nihon,
+            Romanization::Nihon => Romanization::Any,
+            Romanization::Any => Romanization::Hepburn,
+        }
+    }
+}
+
+impl SchedulerKind {
+    pub fn next(self) -> Self {
+        match self {
+            SchedulerKind::Weighted => SchedulerKind::Sm2,
+            SchedulerKind::Sm2 => SchedulerKind::Uniform,
+            SchedulerKind::Uniform => SchedulerKind::Deck,
+            SchedulerKind::Deck => SchedulerKind::Weakest,
+            SchedulerKind::Weakest => SchedulerKind::Weighted,
         }
     }
 }
@@ -272,6 +682,17 @@ pub enum AppMode {
     Initial, // First start, waiting for Enter
     Ready,   // Showing kana, waiting for input
     Paused,  // User entered empty string, waiting for Enter
+    Feedback, // Showing whether the last answer was right, waiting for Enter
+    Summary, // Showing the finished session before quitting
+}
+
+/// The result of the last answer, shown until the learner dismisses it.
+#[derive(Debug, Clone)]
+pub struct Feedback {
+    pub success: bool,
+    pub input: String,
+    pub answer: String,
+    pub revealed: bool,
 }
 
 #[derive(Debug)]
@@ -279,11 +700,41 @@ pub struct AppState {
     pub mode: AppMode,
     pub practice_mode: PracticeMode,
     pub kana_type: KanaType,
+    pub romanization: Romanization,
+    pub exercise: ExerciseMode,
+    pub scheduler: SchedulerKind,
+    pub due_only: bool,
+    // Submit as soon as the answer is decided, without Enter
+    pub instant: bool,
+    pub goal: DailyGoal,
     pub history: UserHistory,
     pub current_kana: Option<String>,
+    pub current_word: Option<&'static Word>,
     pub input_buffer: String,
     pub start_time: Option<DateTime<Utc>>,
     pub expected_romaji: Option<String>,
+    pub choices: Vec<String>,
+    pub notice: Option<String>,
+    pub feedback: Option<Feedback>,
+    // Wrong answers to the prompt on screen
+    pub failed_attempts: u32,
+    // Keys pressed for the prompt on screen, timed from start_time
+    pub keystrokes: Vec<Keystroke>,
+    // Letters of the expected romaji revealed as hints for the prompt on screen
+    pub hints: u32,
+    pub mnemonics: Mnemonics,
+    pub show_mnemonic: bool,
+    // The mnemonic was opened before answering, which counts as a hint
+    pub mnemonic_hint: bool,
+    // Reveal the answer after this many wrong answers; 0 reveals only on request
+    pub reveal_after: u32,
+    // Answers slower than this are tagged idle and left out of response times
+    pub idle_after_ms: f64,
+    pub unsaved_answers: u32,
+    // The session in progress, started by its first answer
+    pub session: Option<SessionRecord>,
+    pub profile: String,
+    pub profiles: Vec<String>,
 }
 
 impl Default for AppState {
@@ -292,11 +743,33 @@ impl Default for AppState {
             mode: AppMode::Initial,
             practice_mode: PracticeMode::Main,
             kana_type: KanaType::Hiragana,
+            romanization: Romanization::default(),
+            exercise: ExerciseMode::default(),
+            scheduler: SchedulerKind::default(),
+            due_only: false,
+            instant: false,
+            goal: DailyGoal::default(),
             history: UserHistory::default(),
             current_kana: None,
+            current_word: None,
             input_buffer: String::new(),
             start_time: None,
             expected_romaji: None,
+            choices: Vec::new(),
+            notice: None,
+            feedback: None,
+            failed_attempts: 0,
+            keystrokes: Vec::new(),
+            hints: 0,
+            mnemonics: Mnemonics::default(),
+            show_mnemonic: false,
+            mnemonic_hint: false,
+            reveal_after: DEFAULT_REVEAL_AFTER,
+            idle_after_ms: DEFAULT_IDLE_AFTER.as_millis() as f64,
+            unsaved_answers: 0,
+            session: None,
+            profile: DEFAULT_PROFILE.to_string(),
+            profiles: Vec::new(),
         }
     }
 }
@@ -0,0 +1,56 @@
+use crate::types::KanaType;
+
+/// A practice word with a short English gloss.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Word {
+    pub kana: &'static str,
+    pub meaning: &'static str,
+}
+
+const fn w(kana: &'static str, meaning: &'static str) -> Word {
+    Word { kana, meaning }
+}
+
+pub const HIRAGANA_WORDS: &[Word] = &[
+    // Sokuon (small っ)
+    w("きって", "stamp"), w("がっこう", "school"), w("ざっし", "magazine"), w("きっぷ", "ticket"),
+    w("いっしょ", "together"), w("ちょっと", "a little"), w("まって", "wait"), w("にっき", "diary"),
+    w("はっぱ", "leaf"), w("せっけん", "soap"), w("けっこん", "marriage"), w("しゅっぱつ", "departure"),
+    w("みっつ", "three things"), w("まっちゃ", "matcha"),
+    // Moraic ん before vowels and y
+    w("きんえん", "no smoking"), w("げんいん", "cause"), w("たんい", "unit"), w("しんや", "late night"),
+    w("こんや", "tonight"), w("ほんや", "bookstore"), w("まんいん", "full"), w("れんあい", "romance"),
+    // Long vowels
+    w("おかあさん", "mother"), w("おとうさん", "father"), w("がくせい", "student"), w("せんせい", "teacher"),
+    w("ありがとう", "thank you"), w("きょう", "today"), w("じゅぎょう", "class"), w("びょういん", "hospital"),
+    w("りょこう", "travel"), w("ぎゅうにゅう", "milk"), w("すうじ", "number"),
+    // Everyday words
+    w("ともだち", "friend"), w("さかな", "fish"), w("さくら", "cherry blossom"), w("ねこ", "cat"),
+    w("いぬ", "dog"), w("やま", "mountain"), w("みず", "water"), w("りんご", "apple"),
+    w("しゃしん", "photo"), w("ひゃく", "hundred"), w("ちゃいろ", "brown"), w("おちゃ", "tea"),
+    w("でんわ", "telephone"), w("つくえ", "desk"), w("ふね", "boat"), w("はなぢ", "nosebleed"),
+    w("つづく", "to continue"), w("ぬいぐるみ", "stuffed toy"), w("めがね", "glasses"), w("へや", "room"),
+];
+
+pub const KATAKANA_WORDS: &[Word] = &[
+    // Long vowel mark
+    w("コーヒー", "coffee"), w("ケーキ", "cake"), w("ビール", "beer"), w("スーパー", "supermarket"),
+    w("コンピューター", "computer"), w("ノート", "notebook"), w("テーブル", "table"), w("ラーメン", "ramen"),
+    w("メニュー", "menu"), w("ニュース", "news"), w("ジュース", "juice"), w("チョコレート", "chocolate"),
+    w("タクシー", "taxi"), w("ゲーム", "game"), w("ソファー", "sofa"), w("フォーク", "fork"),
+    // Sokuon (small ッ)
+    w("ベッド", "bed"), w("ネット", "internet"), w("チケット", "ticket"), w("サッカー", "soccer"),
+    w("ロボット", "robot"), w("ポケット", "pocket"), w("キッチン", "kitchen"), w("マッチ", "match"),
+    // Foreign sounds and everyday words
+    w("カメラ", "camera"), w("パン", "bread"), w("シャツ", "shirt"), w("ホテル", "hotel"),
+    w("テレビ", "television"), w("ヴァイオリン", "violin"), w("ウェブ", "web"), w("シェフ", "chef"),
+    w("ピザ", "pizza"), w("ドア", "door"), w("バス", "bus"), w("ペン", "pen"), w("ツアー", "tour"),
+    w("ファン", "fan"), w("メロン", "melon"), w("ヌードル", "noodle"), w("ソース", "sauce"),
+];
+
+pub fn words_for(kana_type: KanaType) -> &'static [Word] {
+    match kana_type {
+        KanaType::Hiragana => HIRAGANA_WORDS,
+        KanaType::Katakana => KATAKANA_WORDS,
+    }
+}